@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use progress::{ProgressBinding, ProgressRatio, ProgressText};
+use rust_arcade_display::DisplayTheme;
+
+pub struct AccuracyPlugin;
+
+impl Plugin for AccuracyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Accuracy>()
+            .add_startup_system(spawn_accuracy_text);
+    }
+}
+
+/// Share of timing buttons hit with a good or perfect timing.
+#[derive(Default)]
+pub struct Accuracy {
+    pub hits: usize,
+    pub total: usize,
+}
+
+impl Accuracy {
+    pub fn record(&mut self, hit: bool) {
+        self.total += 1;
+        if hit {
+            self.hits += 1;
+        }
+    }
+
+    pub fn ratio(&self) -> f32 {
+        if self.total == 0 {
+            return 0f32;
+        }
+        self.hits as f32 / self.total as f32
+    }
+}
+
+fn spawn_accuracy_text(mut commands: Commands, theme: Res<DisplayTheme>) {
+    let font = theme.font.clone();
+    let progress_entity = commands
+        .spawn()
+        .insert(ProgressRatio { ratio: 0.0 })
+        .insert(ProgressBinding::resource(Accuracy::ratio))
+        .id();
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: "Accuracy ".to_string(),
+                        style: TextStyle {
+                            font: font.clone(),
+                            font_size: 40.0,
+                            color: Color::rgb(0.7, 0.7, 1.0),
+                        },
+                    },
+                    TextSection {
+                        value: "0%".to_string(),
+                        style: TextStyle {
+                            font,
+                            font_size: 60.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                ],
+                alignment: TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            },
+            transform: Transform::from_translation(Vec3::new(0f32, 450f32, 500f32)),
+            ..default()
+        })
+        .insert(
+            ProgressText::new(progress_entity, 100)
+                .with_template("{percent}%")
+                .with_section(1),
+        );
+}
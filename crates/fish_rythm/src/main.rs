@@ -1,7 +1,9 @@
+mod accuracy;
 mod react_timing_buttons;
 mod spawn_timings;
 mod timing_button;
 
+use accuracy::AccuracyPlugin;
 use bevy::{
     app::AppExit,
     prelude::*,
@@ -33,6 +35,7 @@ fn main() {
         .add_plugin(RustArcadePlugin)
         .add_plugin(ReactTimingButtons)
         .add_plugin(ProgressPlugin)
+        .add_plugin(AccuracyPlugin)
         .add_plugin(TimingButtonsPlugin)
         .add_plugin(SpawnTiming)
         .insert_resource(KeyToArcade::default())
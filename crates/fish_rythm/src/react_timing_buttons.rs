@@ -1,14 +1,21 @@
 use bevy::prelude::*;
 use rust_arcade::bevy_rust_arcade::ArcadeInput;
-use rust_arcade_display::InputReaction;
+use rust_arcade_display::{
+    camera_effects::CameraEffect, popup_text::PopupText, FeedbackStyle, FeedbackStyles,
+    FeedbackType, InputReaction,
+};
 
-use crate::timing_button::{TimingButton, TimingButtonExpired, TimingButtonStartTime};
+use crate::{
+    accuracy::Accuracy,
+    timing_button::{TimingButton, TimingButtonExpired, TimingButtonStartTime},
+};
 
 pub struct ReactTimingButtons;
 
 impl Plugin for ReactTimingButtons {
     fn build(&self, app: &mut App) {
         app.add_event::<ValidateButtonEvent>();
+        app.add_startup_system(register_feedback_styles);
         app.add_system_to_stage(CoreStage::PostUpdate, validate_buttons);
         app.add_system_to_stage(CoreStage::PostUpdate, react_to_expired_buttons);
     }
@@ -21,9 +28,28 @@ pub struct ArcadeKey {
 
 pub struct ValidateButtonEvent(pub ArcadeInput);
 
+pub const FEEDBACK_PERFECT: FeedbackType = FeedbackType::Custom("perfect");
+pub const FEEDBACK_MEH: FeedbackType = FeedbackType::Custom("meh");
+
+fn register_feedback_styles(mut styles: ResMut<FeedbackStyles>, asset_server: Res<AssetServer>) {
+    styles.register(
+        FEEDBACK_PERFECT,
+        FeedbackStyle::new(Color::FUCHSIA)
+            .with_particles(Some(asset_server.load("particles/sparkle.particles.json")))
+            .with_camera(CameraEffect {
+                zoom_punch: 0.05f32,
+                flash: Some(Color::rgba(1f32, 1f32, 1f32, 0.15f32)),
+                ..default()
+            }),
+    );
+    styles.register(FEEDBACK_MEH, FeedbackStyle::new(Color::BLUE));
+}
+
 fn validate_buttons(
     mut commands: Commands,
+    mut accuracy: ResMut<Accuracy>,
     mut feedback_events: EventWriter<InputReaction>,
+    mut popups: EventWriter<PopupText>,
     mut ev_validate: EventReader<ValidateButtonEvent>,
     q_button: Query<(Entity, &TimingButton, &TimingButtonStartTime, &ArcadeKey)>,
 ) {
@@ -48,14 +74,28 @@ fn validate_buttons(
                 key: k.key.clone(),
                 feedback: match t {
                     TimingButton::TooEarly => rust_arcade_display::FeedbackType::Bad,
-                    TimingButton::BadEarly => rust_arcade_display::FeedbackType::New,
+                    TimingButton::BadEarly => FEEDBACK_MEH,
                     TimingButton::GoodEarly => rust_arcade_display::FeedbackType::Good,
-                    TimingButton::Perfect => rust_arcade_display::FeedbackType::Menu,
+                    TimingButton::Perfect => FEEDBACK_PERFECT,
                     TimingButton::GoodLate => rust_arcade_display::FeedbackType::Good,
-                    TimingButton::BadLate => rust_arcade_display::FeedbackType::New,
+                    TimingButton::BadLate => FEEDBACK_MEH,
                     TimingButton::TooLate => rust_arcade_display::FeedbackType::Bad,
                 },
             });
+            let judgement = match t {
+                TimingButton::TooEarly => "Too early",
+                TimingButton::BadEarly => "Early",
+                TimingButton::GoodEarly => "Good",
+                TimingButton::Perfect => "PERFECT",
+                TimingButton::GoodLate => "Good",
+                TimingButton::BadLate => "Late",
+                TimingButton::TooLate => "Too late",
+            };
+            popups.send(PopupText::new(k.key.clone(), judgement));
+            accuracy.record(matches!(
+                t,
+                TimingButton::GoodEarly | TimingButton::Perfect | TimingButton::GoodLate
+            ));
             commands.entity(e).despawn();
         } else {
             feedback_events.send(InputReaction {
@@ -68,11 +108,13 @@ fn validate_buttons(
 
 fn react_to_expired_buttons(
     mut commands: Commands,
+    mut accuracy: ResMut<Accuracy>,
     mut feedback_events: EventWriter<InputReaction>,
     mut ev_expired: EventReader<TimingButtonExpired>,
     q_position: Query<(&ArcadeKey)>,
 ) {
     for r in ev_expired.iter() {
+        accuracy.record(false);
         if let Ok(p) = q_position.get(r.0) {
             feedback_events.send(InputReaction {
                 key: p.key.clone(),
@@ -1,6 +1,8 @@
 use std::time::Duration;
 
 use bevy::{math::Vec3Swizzles, prelude::*};
+use game_rng::GameRng;
+use particles::emitter::ParticleEmitter;
 use rand::Rng;
 use rust_arcade::bevy_rust_arcade::ArcadeInput;
 use rust_arcade_display::Reactable;
@@ -31,6 +33,7 @@ impl Plugin for SpawnTiming {
             delay_before_perfect: 2f32,
             delay_before_final_too_late: 4f32,
         });
+        app.init_resource::<GameRng>();
         app.add_system(spawn_timing_buttons);
         app.add_startup_system(spawn_timing_setup);
         app.insert_resource(MappingHelper {
@@ -60,13 +63,15 @@ fn spawn_timing_buttons(
     mappingHelper: Res<MappingHelper>,
     mut commands: Commands,
     time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut game_rng: ResMut<GameRng>,
     mut q_spawn_waves: Query<(&mut SpawnWaves)>,
     mut q_reactable: Query<(&Transform, &Reactable)>,
 ) {
     for (mut s) in q_spawn_waves.iter_mut() {
         s.timer.tick(time.delta());
         if s.timer.just_finished() {
-            let mut rng = rand::thread_rng();
+            let rng = game_rng.stream("spawn_timings");
             let forbidden_inputs = vec![
                 ArcadeInput::ButtonFront1,
                 ArcadeInput::ButtonFront2,
@@ -134,7 +139,12 @@ fn spawn_timing_buttons(
                 .insert(PathDriver {
                     path_target_index: 0,
                     speed,
-                });
+                })
+                .insert(ParticleEmitter::new(
+                    asset_server.load("particles/trail.particles.json"),
+                    Color::GOLD,
+                    40f32,
+                ));
         }
     }
 }
@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Environment variable read for the seed when `--seed` isn't passed.
+pub const SEED_ENV: &str = "ARCADE_SEED";
+
+/// Seeded randomness shared by every system, so a session can be replayed from its seed.
+///
+/// Each subsystem draws from its own named stream, derived from the seed, so adding random
+/// calls in one subsystem doesn't change what the others get.
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<&'static str, ChaCha8Rng>,
+}
+
+impl Default for GameRng {
+    /// Seed from the `--seed <n>` argument, then [`SEED_ENV`], then the current time.
+    fn default() -> Self {
+        let seed = seed_from_args(std::env::args())
+            .or_else(|| std::env::var(SEED_ENV).ok()?.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default()
+            });
+        info!("Random seed: {}", seed);
+        Self::new(seed)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts every stream from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    pub fn stream(&mut self, name: &'static str) -> &mut ChaCha8Rng {
+        let seed = self.seed;
+        self.streams
+            .entry(name)
+            .or_insert_with(|| ChaCha8Rng::seed_from_u64(stream_seed(seed, name)))
+    }
+}
+
+fn seed_from_args(mut args: impl Iterator<Item = String>) -> Option<u64> {
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--seed=") {
+            return value.parse().ok();
+        }
+        if arg == "--seed" {
+            return args.next()?.parse().ok();
+        }
+    }
+    None
+}
+
+/// FNV-1a of the stream name, mixed with the seed. Stable across platforms and versions.
+fn stream_seed(seed: u64, name: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64 ^ seed;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    fn draw(rng: &mut GameRng, name: &'static str) -> Vec<u32> {
+        (0..8).map(|_| rng.stream(name).gen()).collect()
+    }
+
+    #[test]
+    fn same_seed_and_stream_give_same_values() {
+        assert_eq!(
+            draw(&mut GameRng::new(7), "particles"),
+            draw(&mut GameRng::new(7), "particles")
+        );
+        assert_ne!(
+            draw(&mut GameRng::new(7), "particles"),
+            draw(&mut GameRng::new(8), "particles")
+        );
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut alone = GameRng::new(7);
+        let expected = draw(&mut alone, "particles");
+
+        let mut shared = GameRng::new(7);
+        let other = draw(&mut shared, "simon");
+        assert_eq!(draw(&mut shared, "particles"), expected);
+        assert_ne!(other, expected);
+    }
+
+    #[test]
+    fn reseed_restarts_streams() {
+        let mut rng = GameRng::new(7);
+        let first = draw(&mut rng, "particles");
+        rng.reseed(7);
+        assert_eq!(draw(&mut rng, "particles"), first);
+        rng.reseed(9);
+        assert_eq!(rng.seed(), 9);
+        assert_eq!(
+            draw(&mut rng, "particles"),
+            draw(&mut GameRng::new(9), "particles")
+        );
+    }
+
+    #[test]
+    fn parses_seed_argument() {
+        let args = |args: &[&str]| seed_from_args(args.iter().map(|a| a.to_string()));
+        assert_eq!(args(&["game"]), None);
+        assert_eq!(args(&["game", "--seed", "42"]), Some(42));
+        assert_eq!(args(&["game", "--seed=43"]), Some(43));
+        assert_eq!(args(&["game", "--seed", "nope"]), None);
+        assert_eq!(args(&["game", "--seed"]), None);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Piecewise linear curve over a normalized lifetime, keys are `(time, value)` sorted by time.
+///
+/// An empty curve is constant 1.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Curve(pub Vec<(f32, f32)>);
+
+impl Curve {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn sample(&self, t: f32) -> f32 {
+        sample_keys(&self.0, t, |a, b, ratio| a + (b - a) * ratio).unwrap_or(1f32)
+    }
+}
+
+/// Piecewise linear colour gradient over a normalized lifetime, keys are `(time, [r, g, b, a])`
+/// sorted by time.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Gradient(pub Vec<(f32, [f32; 4])>);
+
+impl Gradient {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn sample(&self, t: f32) -> Option<Color> {
+        sample_keys(&self.0, t, |a, b, ratio| {
+            let mut color = a;
+            for (c, b) in color.iter_mut().zip(b) {
+                *c += (b - *c) * ratio;
+            }
+            color
+        })
+        .map(|[r, g, b, a]| Color::rgba(r, g, b, a))
+    }
+}
+
+fn sample_keys<T: Copy>(keys: &[(f32, T)], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let first = keys.first()?;
+    if t <= first.0 {
+        return Some(first.1);
+    }
+    for window in keys.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        if t <= end.0 {
+            let span = end.0 - start.0;
+            let ratio = if span > 0f32 {
+                (t - start.0) / span
+            } else {
+                1f32
+            };
+            return Some(lerp(start.1, end.1, ratio));
+        }
+    }
+    keys.last().map(|k| k.1)
+}
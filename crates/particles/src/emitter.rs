@@ -0,0 +1,81 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use game_rng::GameRng;
+
+use crate::{pool::ParticlePool, preset::EmitterPreset, spawn_particles, Emission, GameClock};
+
+/// Emits particles continuously at the entity position while active, e.g. for trails.
+///
+/// Each emission spawns one particle from `preset`, its `count` is ignored.
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub preset: Handle<EmitterPreset>,
+    pub color: Color,
+    /// Particles per second.
+    pub rate: f32,
+    active: bool,
+    pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(preset: Handle<EmitterPreset>, color: Color, rate: f32) -> Self {
+        Self {
+            preset,
+            color,
+            rate,
+            active: true,
+            pending: 0f32,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.active = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.pending = 0f32;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+pub(crate) fn update_emitters(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    asset_server: Res<AssetServer>,
+    presets: Res<Assets<EmitterPreset>>,
+    mut pool: ResMut<ParticlePool>,
+    mut game_rng: ResMut<GameRng>,
+    mut q_emitters: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+) {
+    let rng = game_rng.stream("particles");
+    for (mut emitter, transform) in q_emitters.iter_mut() {
+        if !emitter.active {
+            continue;
+        }
+        let preset = match presets.get(&emitter.preset) {
+            Some(preset) => preset,
+            None => continue,
+        };
+        emitter.pending += emitter.rate * clock.delta_seconds();
+        let count = emitter.pending.floor();
+        if count < 1f32 {
+            continue;
+        }
+        emitter.pending -= count;
+        let emission = Emission {
+            preset_handle: &emitter.preset,
+            preset,
+            texture: preset
+                .texture
+                .as_ref()
+                .map(|path| asset_server.load(path.as_str())),
+            location: transform.translation.xy(),
+            color: emitter.color,
+            count: count as usize,
+        };
+        spawn_particles(&mut commands, &mut pool, rng, &emission);
+    }
+}
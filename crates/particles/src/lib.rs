@@ -1,4 +1,18 @@
+pub mod curve;
+pub mod emitter;
+pub mod lifetime;
+pub mod pool;
+pub mod preset;
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
 use bevy::prelude::*;
+use emitter::update_emitters;
+use game_rng::GameRng;
+use lifetime::{tick_game_clock, tick_lifetimes, DestroyOnFinish};
+pub use lifetime::{DestroyAfter, GameClock, Lifetime};
+use pool::{ParticlePool, Pooled};
+use preset::{EmitterPreset, EmitterPresetLoader};
 use rand::Rng;
 
 pub struct ParticlesPlugin;
@@ -6,41 +20,146 @@ pub struct ParticlesPlugin;
 impl Plugin for ParticlesPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ParticleExplosion>()
-            .add_system(update_velocity)
+            .init_resource::<ParticlePool>()
+            .init_resource::<GameRng>()
+            .init_resource::<GameClock>()
+            .add_system_to_stage(CoreStage::First, tick_game_clock)
+            .add_system_to_stage(CoreStage::PreUpdate, tick_lifetimes)
+            .add_asset::<EmitterPreset>()
+            .init_asset_loader::<EmitterPresetLoader>()
+            .add_system(update_acceleration)
+            .add_system(update_drag.after(update_acceleration))
+            .add_system(update_velocity.after(update_drag))
+            .add_system(update_angular_velocity)
+            .add_system(update_over_lifetime)
             .add_system(destroy_after)
-            .add_system(handle_particle_events);
+            .add_system(handle_particle_events.after(destroy_after))
+            .add_system(update_emitters.after(handle_particle_events));
+        app.world
+            .resource_mut::<Assets<EmitterPreset>>()
+            .set_untracked(Handle::<EmitterPreset>::default(), EmitterPreset::default());
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ParticleExplosion {
     pub location: Vec2,
     pub color: Color,
+    /// Defaults to the original burst, see [`EmitterPreset::default`].
+    pub preset: Handle<EmitterPreset>,
 }
 
-#[derive(Component)]
-pub struct DestroyAfter {
-    time_to_destroy: f32,
-}
+impl ParticleExplosion {
+    pub fn new(location: Vec2, color: Color) -> Self {
+        Self {
+            location,
+            color,
+            ..default()
+        }
+    }
 
-impl DestroyAfter {
-    pub fn new(time_to_destroy: f32) -> Self {
-        Self { time_to_destroy }
+    pub fn with_preset(mut self, preset: Handle<EmitterPreset>) -> Self {
+        self.preset = preset;
+        self
     }
 }
 
 #[derive(Component)]
 pub struct Velocity(pub Vec2);
 
-fn update_velocity(time: Res<Time>, mut q_vel: Query<(&mut Transform, &Velocity)>) {
+/// Added to [`Velocity`] every second, e.g. gravity.
+#[derive(Component)]
+pub struct Acceleration(pub Vec2);
+
+/// Exponential decay rate of [`Velocity`]: it is multiplied by `exp(-drag * dt)` each frame,
+/// so it is divided by `e` every `1 / drag` seconds.
+#[derive(Component)]
+pub struct Drag(pub f32);
+
+/// Rotation around z, in radians per second.
+#[derive(Component)]
+pub struct AngularVelocity(pub f32);
+
+/// Drives colour and size from the curves of `preset`, along the entity [`Lifetime`].
+#[derive(Component)]
+pub struct OverLifetime {
+    pub preset: Handle<EmitterPreset>,
+    pub base_color: Color,
+    pub base_size: f32,
+}
+
+fn update_acceleration(clock: Res<GameClock>, mut q_vel: Query<(&mut Velocity, &Acceleration)>) {
+    for (mut vel, acc) in q_vel.iter_mut() {
+        vel.0 += acc.0 * clock.delta_seconds();
+    }
+}
+
+fn update_drag(clock: Res<GameClock>, mut q_vel: Query<(&mut Velocity, &Drag)>) {
+    for (mut vel, drag) in q_vel.iter_mut() {
+        vel.0 *= (-drag.0 * clock.delta_seconds()).exp();
+    }
+}
+
+fn update_angular_velocity(
+    clock: Res<GameClock>,
+    mut q_vel: Query<(&mut Transform, &AngularVelocity)>,
+) {
+    for (mut t, vel) in q_vel.iter_mut() {
+        t.rotate(Quat::from_rotation_z(vel.0 * clock.delta_seconds()));
+    }
+}
+
+fn update_over_lifetime(
+    presets: Res<Assets<EmitterPreset>>,
+    mut q_particles: Query<(&mut Sprite, &OverLifetime, &Lifetime)>,
+) {
+    for (mut sprite, over, lifetime) in q_particles.iter_mut() {
+        let preset = match presets.get(&over.preset) {
+            Some(preset) => preset,
+            None => continue,
+        };
+        let t = lifetime.fraction();
+        let mut color = preset
+            .color_over_lifetime
+            .sample(t)
+            .unwrap_or(over.base_color);
+        color.set_a(color.a() * preset.alpha_over_lifetime.sample(t));
+        sprite.color = color;
+        sprite.custom_size = Some(Vec2::splat(
+            over.base_size * preset.size_over_lifetime.sample(t),
+        ));
+    }
+}
+
+fn update_velocity(clock: Res<GameClock>, mut q_vel: Query<(&mut Transform, &Velocity)>) {
     for (mut t, vel) in q_vel.iter_mut() {
-        t.translation += (vel.0 * time.delta_seconds()).extend(0f32);
+        t.translation += (vel.0 * clock.delta_seconds()).extend(0f32);
     }
 }
 
-fn destroy_after(mut commands: Commands, time: Res<Time>, q_des: Query<(Entity, &DestroyAfter)>) {
-    for (e, d) in q_des.iter() {
-        if d.time_to_destroy <= time.seconds_since_startup() as f32 {
+fn destroy_after(
+    mut commands: Commands,
+    mut pool: ResMut<ParticlePool>,
+    q_des: Query<(Entity, &Lifetime, Option<&Pooled>), With<DestroyOnFinish>>,
+) {
+    for (e, lifetime, pooled) in q_des.iter() {
+        if !lifetime.is_finished() {
+            continue;
+        }
+        if pooled.is_some() && pool.release(e) {
+            commands
+                .entity(e)
+                .remove_bundle::<(
+                    Lifetime,
+                    DestroyOnFinish,
+                    Velocity,
+                    Acceleration,
+                    Drag,
+                    AngularVelocity,
+                    OverLifetime,
+                )>()
+                .insert(Visibility { is_visible: false });
+        } else {
             commands.entity(e).despawn();
         }
     }
@@ -48,27 +167,145 @@ fn destroy_after(mut commands: Commands, time: Res<Time>, q_des: Query<(Entity,
 
 fn handle_particle_events(
     mut commands: Commands,
-    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    presets: Res<Assets<EmitterPreset>>,
+    mut pool: ResMut<ParticlePool>,
+    mut game_rng: ResMut<GameRng>,
     mut evt_particles: EventReader<ParticleExplosion>,
 ) {
+    let rng = game_rng.stream("particles");
     for p in evt_particles.iter() {
-        let time_to_die = time.seconds_since_startup() as f32 + 1f32;
-        for i in 0..40 {
-            let mut offset: Vec2 = rand::thread_rng().gen::<(f32, f32)>().into();
-            offset -= Vec2::new(0.5f32, 0.5f32);
-            let position = p.location + (offset * 50f32);
-            commands
-                .spawn_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: p.color,
-                        custom_size: Some(Vec2::splat(50f32)),
-                        ..default()
-                    },
-                    transform: Transform::from_translation(position.extend(1f32)),
-                    ..default()
-                })
-                .insert(Velocity(offset * 2000f32))
-                .insert(DestroyAfter::new(time_to_die));
+        let preset = match presets.get(&p.preset) {
+            Some(preset) => preset,
+            None => {
+                warn!("Particle preset {:?} is not loaded", p.preset);
+                continue;
+            }
+        };
+        let emission = Emission {
+            preset_handle: &p.preset,
+            preset,
+            texture: preset
+                .texture
+                .as_ref()
+                .map(|path| asset_server.load(path.as_str())),
+            location: p.location,
+            color: p.color,
+            count: preset.count,
+        };
+        spawn_particles(&mut commands, &mut pool, rng, &emission);
+    }
+}
+
+/// Particles to spawn at once from a preset.
+pub(crate) struct Emission<'a> {
+    pub preset_handle: &'a Handle<EmitterPreset>,
+    pub preset: &'a EmitterPreset,
+    pub texture: Option<Handle<Image>>,
+    pub location: Vec2,
+    pub color: Color,
+    pub count: usize,
+}
+
+pub(crate) fn spawn_particles(
+    commands: &mut Commands,
+    pool: &mut ParticlePool,
+    rng: &mut impl Rng,
+    emission: &Emission,
+) {
+    let preset = emission.preset;
+    let half_diagonal = preset.area * FRAC_1_SQRT_2;
+    let acceleration = Vec2::from(preset.acceleration);
+    for _ in 0..emission.count {
+        let mut offset: Vec2 = rng.gen::<(f32, f32)>().into();
+        offset -= Vec2::new(0.5f32, 0.5f32);
+        offset *= preset.area;
+        let position = emission.location + offset;
+        let velocity = if preset.outward {
+            let ratio = if half_diagonal > 0f32 {
+                offset.length() / half_diagonal
+            } else {
+                0f32
+            };
+            offset.normalize_or_zero()
+                * (preset.speed.0 + (preset.speed.1 - preset.speed.0) * ratio)
+        } else {
+            let angle = preset.direction + (rng.gen::<f32>() - 0.5f32) * preset.spread;
+            Vec2::new(angle.cos(), angle.sin()) * rng.gen_range(preset.speed.0..=preset.speed.1)
+        };
+        let size = rng.gen_range(preset.size.0..=preset.size.1);
+        let mut sprite = SpriteBundle {
+            sprite: Sprite {
+                color: emission.color,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(preset.z)),
+            ..default()
+        };
+        if let Some(texture) = &emission.texture {
+            sprite.texture = texture.clone();
+        }
+        let entity = pool.acquire(commands);
+        let mut particle = commands.entity(entity);
+        particle
+            .remove_bundle::<(Acceleration, Drag, AngularVelocity, OverLifetime)>()
+            .insert_bundle(sprite)
+            .insert(Pooled)
+            .insert(Velocity(velocity))
+            .insert_bundle(DestroyAfter::new(preset.lifetime));
+        if acceleration != Vec2::ZERO {
+            particle.insert(Acceleration(acceleration));
+        }
+        if preset.drag > 0f32 {
+            particle.insert(Drag(preset.drag));
         }
+        if preset.angular_speed != (0f32, 0f32) {
+            particle.insert(AngularVelocity(
+                rng.gen_range(preset.angular_speed.0..=preset.angular_speed.1),
+            ));
+        }
+        if preset.has_lifetime_curves() {
+            particle.insert(OverLifetime {
+                preset: emission.preset_handle.clone(),
+                base_color: emission.color,
+                base_size: size,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, ecs::event::Events};
+
+    use super::*;
+
+    fn spawn_positions(seed: u64) -> Vec<(Vec3, Vec2)> {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .insert_resource(GameRng::new(seed))
+            .add_plugin(ParticlesPlugin);
+        app.world
+            .resource_mut::<Events<ParticleExplosion>>()
+            .send(ParticleExplosion::new(
+                Vec2::new(10f32, 20f32),
+                Color::WHITE,
+            ));
+        app.update();
+        let mut query = app.world.query::<(&Transform, &Velocity)>();
+        query
+            .iter(&app.world)
+            .map(|(transform, velocity)| (transform.translation, velocity.0))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_spawns_same_particles() {
+        let positions = spawn_positions(42);
+        assert!(!positions.is_empty());
+        assert_eq!(positions, spawn_positions(42));
+        assert_ne!(positions, spawn_positions(43));
     }
 }
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// Game time, which can be paused or slowed down independently of the real [`Time`].
+///
+/// Particles and [`Lifetime`]s advance with it, so they freeze while a game is paused.
+pub struct GameClock {
+    delta: Duration,
+    elapsed: f64,
+    scale: f32,
+    paused: bool,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            elapsed: 0f64,
+            scale: 1f32,
+            paused: false,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Game seconds elapsed since startup, not counting pauses.
+    pub fn seconds_since_startup(&self) -> f64 {
+        self.elapsed
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Speed of game time relative to real time, 1 by default.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0f32);
+    }
+}
+
+pub(crate) fn tick_game_clock(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    clock.delta = if clock.paused {
+        Duration::ZERO
+    } else {
+        time.delta().mul_f32(clock.scale)
+    };
+    clock.elapsed += clock.delta.as_secs_f64();
+}
+
+/// Time an entity has been alive for, advancing with the [`GameClock`].
+#[derive(Component, Debug, Clone)]
+pub struct Lifetime {
+    timer: Timer,
+}
+
+impl Lifetime {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, false),
+        }
+    }
+
+    /// From 0 when spawned to 1 when finished.
+    pub fn fraction(&self) -> f32 {
+        if self.timer.duration().is_zero() {
+            return 1f32;
+        }
+        self.timer.percent()
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.timer.elapsed_secs()
+    }
+
+    pub fn remaining_seconds(&self) -> f32 {
+        (self.timer.duration() - self.timer.elapsed()).as_secs_f32()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.timer.finished()
+    }
+}
+
+/// Marks entities to despawn once their [`Lifetime`] is finished.
+#[derive(Component)]
+pub struct DestroyOnFinish;
+
+/// Despawns the entity after `seconds` of game time.
+#[derive(Bundle)]
+pub struct DestroyAfter {
+    pub lifetime: Lifetime,
+    pub destroy: DestroyOnFinish,
+}
+
+impl DestroyAfter {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            lifetime: Lifetime::new(seconds),
+            destroy: DestroyOnFinish,
+        }
+    }
+}
+
+pub(crate) fn tick_lifetimes(clock: Res<GameClock>, mut q_lifetimes: Query<&mut Lifetime>) {
+    for mut lifetime in q_lifetimes.iter_mut() {
+        lifetime.timer.tick(clock.delta());
+    }
+}
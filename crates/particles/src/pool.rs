@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+/// Keeps finished particles around to reuse their entities, and caps the number of live
+/// particles to `budget` by recycling the oldest first.
+pub struct ParticlePool {
+    /// Maximum number of live particles.
+    pub budget: usize,
+    /// When false, finished particles are despawned instead of kept for reuse.
+    pub recycle: bool,
+    free: Vec<Entity>,
+    /// Live particles in spawn order, entries whose generation changed are stale.
+    alive: VecDeque<(Entity, u32)>,
+    generations: HashMap<Entity, u32>,
+    next_generation: u32,
+}
+
+impl Default for ParticlePool {
+    fn default() -> Self {
+        Self {
+            budget: 2000,
+            recycle: true,
+            free: Vec::new(),
+            alive: VecDeque::new(),
+            generations: HashMap::new(),
+            next_generation: 0,
+        }
+    }
+}
+
+/// Marks particles owned by the [`ParticlePool`].
+#[derive(Component)]
+pub struct Pooled;
+
+impl ParticlePool {
+    pub fn alive_count(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns an entity to (re)use for a new particle, evicting the oldest one if over budget.
+    /// The caller is responsible for resetting the components of reused entities.
+    pub(crate) fn acquire(&mut self, commands: &mut Commands) -> Entity {
+        if self.budget <= self.alive_count() {
+            if let Some(oldest) = self.pop_oldest() {
+                if self.recycle {
+                    self.track(oldest);
+                    return oldest;
+                }
+                commands.entity(oldest).despawn();
+            }
+        }
+        let entity = self.free.pop().unwrap_or_else(|| commands.spawn().id());
+        self.track(entity);
+        entity
+    }
+
+    /// Called when a particle is finished, returns whether its entity was kept for reuse.
+    pub(crate) fn release(&mut self, entity: Entity) -> bool {
+        if self.generations.remove(&entity).is_none() {
+            return false;
+        }
+        while let Some((front, generation)) = self.alive.front() {
+            if self.generations.get(front) == Some(generation) {
+                break;
+            }
+            self.alive.pop_front();
+        }
+        if self.recycle {
+            self.free.push(entity);
+        }
+        self.recycle
+    }
+
+    fn track(&mut self, entity: Entity) {
+        let generation = self.next_generation;
+        self.next_generation = self.next_generation.wrapping_add(1);
+        self.generations.insert(entity, generation);
+        self.alive.push_back((entity, generation));
+    }
+
+    fn pop_oldest(&mut self) -> Option<Entity> {
+        while let Some((entity, generation)) = self.alive.pop_front() {
+            if self.generations.get(&entity) == Some(&generation) {
+                self.generations.remove(&entity);
+                return Some(entity);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::TAU;
+
+use anyhow::{bail, ensure};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use crate::curve::{Curve, Gradient};
+
+/// Describes how a [`crate::ParticleExplosion`] spawns its particles.
+///
+/// Presets are loaded from `.particles.json` files, missing fields take the default values,
+/// which reproduce the original burst of 40 squares.
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "0f1d1f5a-4b7e-4a8c-9c53-36b2f1e3e0a1"]
+#[serde(default)]
+pub struct EmitterPreset {
+    pub count: usize,
+    /// Side of the square, centred on the explosion, where particles appear.
+    pub area: f32,
+    /// Min and max side of the particle sprites.
+    pub size: (f32, f32),
+    /// Min and max speed, in world units per second.
+    pub speed: (f32, f32),
+    /// When true, particles fly away from the centre with a speed growing with their distance
+    /// to it; otherwise they pick a random speed and a direction within `spread`.
+    pub outward: bool,
+    /// Angle in radians of the initial direction, 0 is right.
+    pub direction: f32,
+    /// Full angle in radians around `direction` particles can go to.
+    pub spread: f32,
+    /// Constant acceleration, like gravity, in world units per second squared.
+    pub acceleration: (f32, f32),
+    /// Exponential decay rate of the velocity, see [`crate::Drag`].
+    pub drag: f32,
+    /// Min and max rotation speed, in radians per second.
+    pub angular_speed: (f32, f32),
+    /// Seconds before a particle is removed.
+    pub lifetime: f32,
+    /// Replaces the explosion colour over the particle lifetime.
+    pub color_over_lifetime: Gradient,
+    /// Multiplies the colour alpha over the particle lifetime.
+    pub alpha_over_lifetime: Curve,
+    /// Multiplies the particle size over its lifetime.
+    pub size_over_lifetime: Curve,
+    /// Asset path of the particle image, plain squares when `None`.
+    pub texture: Option<String>,
+    pub z: f32,
+}
+
+impl EmitterPreset {
+    pub fn has_lifetime_curves(&self) -> bool {
+        !self.color_over_lifetime.is_empty()
+            || !self.alpha_over_lifetime.is_empty()
+            || !self.size_over_lifetime.is_empty()
+    }
+
+    /// Checks the values which would panic when spawning particles.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, (min, max)) in [
+            ("size", self.size),
+            ("speed", self.speed),
+            ("angular_speed", self.angular_speed),
+        ] {
+            if min.is_nan() || max.is_nan() || min > max {
+                bail!("`{}` min {} is above its max {}", name, min, max);
+            }
+        }
+        ensure!(
+            self.lifetime.is_finite() && self.lifetime >= 0f32,
+            "`lifetime` must be a positive number of seconds, got {}",
+            self.lifetime
+        );
+        Ok(())
+    }
+}
+
+impl Default for EmitterPreset {
+    fn default() -> Self {
+        Self {
+            count: 40,
+            area: 50f32,
+            size: (50f32, 50f32),
+            speed: (0f32, 2000f32 * std::f32::consts::FRAC_1_SQRT_2),
+            outward: true,
+            direction: 0f32,
+            spread: TAU,
+            acceleration: (0f32, 0f32),
+            drag: 0f32,
+            angular_speed: (0f32, 0f32),
+            lifetime: 1f32,
+            color_over_lifetime: Gradient::default(),
+            alpha_over_lifetime: Curve::default(),
+            size_over_lifetime: Curve::default(),
+            texture: None,
+            z: 1f32,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EmitterPresetLoader;
+
+impl AssetLoader for EmitterPresetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let preset: EmitterPreset = serde_json::from_slice(bytes)?;
+            preset.validate()?;
+            load_context.set_default_asset(LoadedAsset::new(preset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particles.json"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> EmitterPreset {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn default_preset_is_valid() {
+        assert!(EmitterPreset::default().validate().is_ok());
+    }
+
+    #[test]
+    fn inverted_range_names_the_field() {
+        let error = parse(r#"{ "speed": [300.0, 100.0] }"#)
+            .validate()
+            .unwrap_err();
+        assert!(error.to_string().contains("`speed`"), "{}", error);
+    }
+
+    #[test]
+    fn negative_lifetime_is_rejected() {
+        let error = parse(r#"{ "lifetime": -1.0 }"#).validate().unwrap_err();
+        assert!(error.to_string().contains("`lifetime`"), "{}", error);
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, sprite::Anchor};
+
+use crate::{
+    visuals::{ProgressColor, ProgressSegments},
+    ProgressRatio, ProgressScale, ProgressText,
+};
+
+/// Root of a bar spawned by [`ProgressBarBuilder`], which holds its [`ProgressRatio`].
+///
+/// Hiding it hides the whole bar, and `despawn_recursive` removes it.
+#[derive(Component)]
+pub struct ProgressBar;
+
+/// Spawns a horizontal bar filling from the left, with a background and an optional text.
+pub struct ProgressBarBuilder {
+    size: Vec2,
+    translation: Vec3,
+    anchor: Anchor,
+    background: Color,
+    fill: Color,
+    fill_gradient: Option<Vec<(f32, Color)>>,
+    segments: Option<BarSegments>,
+    text: Option<BarText>,
+}
+
+struct BarSegments {
+    count: usize,
+    gap: f32,
+    empty: Color,
+}
+
+struct BarText {
+    template: String,
+    max: usize,
+    style: TextStyle,
+    offset: Vec2,
+}
+
+impl ProgressBarBuilder {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            translation: Vec3::ZERO,
+            anchor: Anchor::Center,
+            background: Color::BLACK,
+            fill: Color::WHITE,
+            fill_gradient: None,
+            segments: None,
+            text: None,
+        }
+    }
+
+    /// Where the `anchor` point of the bar is, the fill is drawn just above `translation.z`.
+    pub fn at(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_colors(mut self, background: Color, fill: Color) -> Self {
+        self.background = background;
+        self.fill = fill;
+        self
+    }
+
+    /// Colours the fill along a gradient instead, see [`ProgressColor`].
+    pub fn with_fill_gradient(mut self, gradient: Vec<(f32, Color)>) -> Self {
+        self.fill_gradient = Some(gradient);
+        self
+    }
+
+    /// Fills `count` discrete ticks instead of a continuous fill, see [`ProgressSegments`].
+    /// Ticks not reached yet are coloured `empty`, the fill gradient doesn't apply to them.
+    pub fn with_segments(mut self, count: usize, gap: f32, empty: Color) -> Self {
+        self.segments = Some(BarSegments { count, gap, empty });
+        self
+    }
+
+    /// Text centred on the bar, `template` as for [`ProgressText`].
+    pub fn with_text(mut self, template: impl Into<String>, max: usize, style: TextStyle) -> Self {
+        self.text = Some(BarText {
+            template: template.into(),
+            max,
+            style,
+            offset: Vec2::ZERO,
+        });
+        self
+    }
+
+    /// Moves the text away from the centre of the bar.
+    pub fn with_text_offset(mut self, offset: Vec2) -> Self {
+        if let Some(text) = &mut self.text {
+            text.offset = offset;
+        }
+        self
+    }
+
+    /// Spawns the bar, the returned root is the progress entity to drive.
+    pub fn spawn<'w, 's, 'a>(
+        self,
+        commands: &'a mut Commands<'w, 's>,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let root = commands
+            .spawn_bundle(TransformBundle::from_transform(
+                Transform::from_translation(self.translation),
+            ))
+            .insert(Visibility::default())
+            .insert(ProgressRatio { ratio: 0.0 })
+            .insert(ProgressBar)
+            .id();
+        let center = -self.anchor.as_vec() * self.size;
+        let left = Vec3::new(center.x - self.size.x / 2f32, center.y, 0f32);
+        let mut children = vec![commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: self.background,
+                    custom_size: Some(self.size),
+                    anchor: Anchor::CenterLeft,
+                    ..default()
+                },
+                transform: Transform::from_translation(left),
+                ..default()
+            })
+            .id()];
+        match self.segments {
+            Some(segments) => children.push(
+                commands
+                    .spawn_bundle(TransformBundle::from_transform(
+                        Transform::from_translation(left + Vec3::Z),
+                    ))
+                    .insert(Visibility::default())
+                    .insert(ProgressSegments {
+                        progress_entity: root,
+                        count: segments.count,
+                        size: self.size,
+                        gap: segments.gap,
+                        filled: self.fill,
+                        empty: segments.empty,
+                    })
+                    .id(),
+            ),
+            None => {
+                let mut fill = commands.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: self.fill,
+                        custom_size: Some(self.size),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(left + Vec3::Z),
+                    ..default()
+                });
+                fill.insert(ProgressScale {
+                    scale_base: Vec3::new(0f32, 1f32, 1f32),
+                    scale_mult: Vec3::new(1f32, 0f32, 0f32),
+                    progress_entity: root,
+                });
+                if let Some(gradient) = self.fill_gradient {
+                    fill.insert(ProgressColor {
+                        progress_entity: root,
+                        gradient,
+                    });
+                }
+                children.push(fill.id());
+            }
+        }
+        if let Some(text) = self.text {
+            let alignment = TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            };
+            children.push(
+                commands
+                    .spawn_bundle(Text2dBundle {
+                        text: Text::with_section(text.template.clone(), text.style, alignment),
+                        transform: Transform::from_translation((center + text.offset).extend(2f32)),
+                        ..default()
+                    })
+                    .insert(ProgressText::new(root, text.max).with_template(text.template))
+                    .id(),
+            );
+        }
+        let mut root = commands.entity(root);
+        root.push_children(&children);
+        root
+    }
+}
+
+/// Hides or shows every descendant of a bar with it, since visibility isn't inherited.
+pub(crate) fn propagate_bar_visibility(
+    q_bars: Query<(&Visibility, &Children), With<ProgressBar>>,
+    q_children: Query<&Children>,
+    mut q_visibility: Query<&mut Visibility, Without<ProgressBar>>,
+) {
+    for (visibility, children) in q_bars.iter() {
+        let mut stack: Vec<Entity> = children.iter().copied().collect();
+        while let Some(child) = stack.pop() {
+            if let Ok(mut child_visibility) = q_visibility.get_mut(child) {
+                if child_visibility.is_visible != visibility.is_visible {
+                    child_visibility.is_visible = visibility.is_visible;
+                }
+            }
+            if let Ok(grandchildren) = q_children.get(child) {
+                stack.extend(grandchildren.iter().copied());
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{visuals::ProgressSegments, ProgressRatio, ProgressText};
+
+type ReadRatio = Box<dyn Fn(&World) -> Option<f32> + Send + Sync>;
+type ReadMax = Box<dyn Fn(&World) -> Option<usize> + Send + Sync>;
+
+/// Drives the [`ProgressRatio`] of its entity from any value in the world, e.g. a resource
+/// or the component of another entity, so games don't need their own sync system.
+#[derive(Component)]
+pub struct ProgressBinding {
+    ratio: ReadRatio,
+    max: Option<ReadMax>,
+}
+
+impl ProgressBinding {
+    /// `ratio` returns `None` to leave the progress as is.
+    pub fn new(ratio: impl Fn(&World) -> Option<f32> + Send + Sync + 'static) -> Self {
+        Self {
+            ratio: Box::new(ratio),
+            max: None,
+        }
+    }
+
+    /// Follows a resource.
+    pub fn resource<R: Send + Sync + 'static>(
+        ratio: impl Fn(&R) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(move |world| world.get_resource::<R>().map(&ratio))
+    }
+
+    /// Follows a component of `source`.
+    pub fn component<C: Component>(
+        source: Entity,
+        ratio: impl Fn(&C) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(move |world| world.get::<C>(source).map(&ratio))
+    }
+
+    /// Also sets the `max` of the [`ProgressText`]s, and the `count` of the [`ProgressSegments`]
+    /// showing this progress.
+    pub fn with_max(
+        mut self,
+        max: impl Fn(&World) -> Option<usize> + Send + Sync + 'static,
+    ) -> Self {
+        self.max = Some(Box::new(max));
+        self
+    }
+}
+
+pub(crate) fn update_bindings(world: &mut World) {
+    let mut q_bindings = world.query::<(Entity, &ProgressBinding)>();
+    let values: Vec<(Entity, Option<f32>, Option<usize>)> = q_bindings
+        .iter(world)
+        .map(|(entity, binding)| {
+            let max = binding.max.as_ref().and_then(|max| max(world));
+            (entity, (binding.ratio)(world), max)
+        })
+        .collect();
+    for (entity, ratio, max) in values {
+        if let (Some(ratio), Some(mut progress)) = (ratio, world.get_mut::<ProgressRatio>(entity)) {
+            if progress.ratio != ratio {
+                progress.ratio = ratio;
+            }
+        }
+        if let Some(max) = max {
+            let mut q_texts = world.query::<&mut ProgressText>();
+            for mut text in q_texts.iter_mut(world) {
+                if text.progress_entity == entity && text.max != max {
+                    text.max = max;
+                }
+            }
+            let mut q_segments = world.query::<&mut ProgressSegments>();
+            for mut segments in q_segments.iter_mut(world) {
+                if segments.progress_entity == entity && segments.count != max {
+                    segments.count = max;
+                }
+            }
+        }
+    }
+}
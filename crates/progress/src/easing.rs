@@ -0,0 +1,34 @@
+use std::f32::consts::PI;
+
+/// Maps a linear ratio from 0 to 1 onto a curve, to make progress feel less mechanical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicOut,
+    SineInOut,
+}
+
+impl Easing {
+    /// `t` is clamped between 0 and 1.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0f32, 1f32);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2f32 - t),
+            Easing::QuadInOut => {
+                if t < 0.5f32 {
+                    2f32 * t * t
+                } else {
+                    1f32 - (-2f32 * t + 2f32).powi(2) / 2f32
+                }
+            }
+            Easing::CubicOut => 1f32 - (1f32 - t).powi(3),
+            Easing::SineInOut => -((PI * t).cos() - 1f32) / 2f32,
+        }
+    }
+}
@@ -1,4 +1,15 @@
+pub mod bar;
+pub mod binding;
+pub mod easing;
+pub mod visuals;
+
+use bar::propagate_bar_visibility;
+pub use bar::{ProgressBar, ProgressBarBuilder};
 use bevy::prelude::*;
+use binding::update_bindings;
+pub use binding::ProgressBinding;
+pub use easing::Easing;
+use visuals::{spawn_segments, update_color, update_radial, update_segments};
 
 pub struct ProgressPlugin;
 
@@ -8,11 +19,51 @@ pub struct ProgressRatio {
     pub ratio: f32,
 }
 
+/// Writes the progress of `progress_entity` into a [`Text`] section, from a template where
+/// `{current}`, `{max}`, `{percent}` and `{remaining}` (whole seconds left on a
+/// [`ProgressTime`]) are replaced.
 #[derive(Component)]
 pub struct ProgressText {
     pub max: usize,
     pub progress_entity: Entity,
+    pub template: String,
+    /// Index of the text section to write to.
+    pub section: usize,
+}
+
+impl ProgressText {
+    pub fn new(progress_entity: Entity, max: usize) -> Self {
+        Self {
+            max,
+            progress_entity,
+            template: "{current}/{max}".to_string(),
+            section: 0,
+        }
+    }
+
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    pub fn with_section(mut self, section: usize) -> Self {
+        self.section = section;
+        self
+    }
+
+    pub fn format(&self, ratio: f32, remaining_seconds: f32) -> String {
+        let current = (self.max as f32 * ratio).round() as usize;
+        self.template
+            .replace("{current}", &current.to_string())
+            .replace("{max}", &self.max.to_string())
+            .replace("{percent}", &((ratio * 100f32).round() as i32).to_string())
+            .replace(
+                "{remaining}",
+                &(remaining_seconds.max(0f32).ceil() as usize).to_string(),
+            )
+    }
 }
+
 #[derive(Component)]
 pub struct ProgressScale {
     pub scale_base: Vec3,
@@ -20,49 +71,274 @@ pub struct ProgressScale {
     pub progress_entity: Entity,
 }
 
+/// Drives the [`ProgressRatio`] of its entity over `duration` seconds.
 #[derive(Component, Default)]
 pub struct ProgressTime {
-    pub start_time: f32,
     pub duration: f32,
+    pub elapsed: f32,
+    /// Inactive progresses are stopped, paused ones are still considered running.
     pub active: bool,
+    pub paused: bool,
+    pub direction: ProgressDirection,
+    /// Starts over when reaching `duration`, sending [`ProgressCompleted`] on every loop.
+    pub looping: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressDirection {
+    #[default]
+    Forward,
+    /// Drains back to 0, where the progress stops.
+    Reverse,
+}
+
+impl ProgressTime {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            ..default()
+        }
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Starts over from 0.
+    pub fn start(&mut self) {
+        self.elapsed = 0f32;
+        self.active = true;
+        self.paused = false;
+        self.direction = ProgressDirection::Forward;
+    }
+
+    pub fn stop(&mut self) {
+        self.elapsed = 0f32;
+        self.active = false;
+        self.paused = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Drains the progress back to 0 from where it is.
+    pub fn reverse(&mut self) {
+        self.direction = ProgressDirection::Reverse;
+        self.paused = false;
+        self.active = self.elapsed > 0f32;
+    }
+
+    pub fn remaining_seconds(&self) -> f32 {
+        match self.direction {
+            ProgressDirection::Forward => self.duration - self.elapsed,
+            ProgressDirection::Reverse => self.elapsed,
+        }
+    }
+}
+
+/// Eases and smooths the ratio shown by [`ProgressText`] and [`ProgressScale`],
+/// on the same entity as the [`ProgressRatio`].
+#[derive(Component, Default)]
+pub struct ProgressDisplay {
+    pub easing: Easing,
+    /// How fast the shown value catches up with the eased ratio, 0 to follow it instantly.
+    pub smoothing: f32,
+    value: f32,
+}
+
+impl ProgressDisplay {
+    pub fn new(easing: Easing) -> Self {
+        Self {
+            easing,
+            ..default()
+        }
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// Value currently shown, from 0 to 1.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+/// Where a progress is at, to send [`ProgressStarted`], [`ProgressCompleted`] and
+/// [`ProgressCancelled`]. Added by the [`ProgressPlugin`] to every [`ProgressRatio`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressState {
+    Idle,
+    Running,
+    Completed,
+}
+
+/// Sent when a [`ProgressTime`] is activated, or a [`ProgressRatio`] goes above 0.
+pub struct ProgressStarted {
+    pub entity: Entity,
+}
+
+/// Sent when a running progress reaches a ratio of 1, or loops.
+pub struct ProgressCompleted {
+    pub entity: Entity,
+}
+
+/// Sent when a running progress is stopped, drained, or goes back to 0, before completing.
+pub struct ProgressCancelled {
+    pub entity: Entity,
 }
 
 impl Plugin for ProgressPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(update_progress)
-            .add_system(update_text)
-            .add_system(update_scale);
+        app.add_event::<ProgressStarted>()
+            .add_event::<ProgressCompleted>()
+            .add_event::<ProgressCancelled>()
+            .add_system(update_bindings.exclusive_system())
+            .add_system(update_progress)
+            .add_system(add_progress_state)
+            .add_system(update_progress_state.after(update_progress))
+            .add_system(update_display.after(update_progress))
+            .add_system(update_text.after(update_display))
+            .add_system(update_scale.after(update_display))
+            .add_system(update_radial.after(update_display))
+            .add_system(spawn_segments)
+            .add_system(update_segments.after(update_display))
+            .add_system(update_color.after(update_display))
+            .add_system(propagate_bar_visibility);
     }
 }
 
 fn update_progress(
     time: Res<Time>,
-    mut q_progress: Query<(&mut ProgressTime, &mut ProgressRatio)>,
+    mut evt_completed: EventWriter<ProgressCompleted>,
+    mut q_progress: Query<(Entity, &mut ProgressTime, &mut ProgressRatio)>,
 ) {
-    for (progress, mut ratio) in q_progress.iter_mut() {
-        if !progress.active {
+    for (entity, mut progress, mut ratio) in q_progress.iter_mut() {
+        if !progress.active || progress.paused {
             continue;
         }
-        ratio.ratio =
-            (time.seconds_since_startup() as f32 - progress.start_time) / progress.duration;
+        match progress.direction {
+            ProgressDirection::Forward => {
+                progress.elapsed += time.delta_seconds();
+                if progress.looping && progress.elapsed >= progress.duration {
+                    progress.elapsed %= progress.duration.max(f32::EPSILON);
+                    evt_completed.send(ProgressCompleted { entity });
+                }
+                progress.elapsed = progress.elapsed.min(progress.duration);
+            }
+            ProgressDirection::Reverse => {
+                progress.elapsed = (progress.elapsed - time.delta_seconds()).max(0f32);
+                if progress.elapsed <= 0f32 {
+                    progress.active = false;
+                }
+            }
+        }
+        ratio.ratio = if progress.duration > 0f32 {
+            progress.elapsed / progress.duration
+        } else {
+            1f32
+        };
     }
 }
-fn update_text(q_data: Query<&ProgressRatio>, mut q_progress: Query<(&mut Text, &ProgressText)>) {
-    for (mut text, max) in q_progress.iter_mut() {
-        if let Ok(progress) = q_data.get_component::<ProgressRatio>(max.progress_entity) {
-            let number = (max.max as f32 * progress.ratio) as usize;
-            text.sections[0].value = format!("{}/{}", number, max.max);
+
+fn add_progress_state(
+    mut commands: Commands,
+    q_new: Query<Entity, (With<ProgressRatio>, Without<ProgressState>)>,
+) {
+    for e in q_new.iter() {
+        commands.entity(e).insert(ProgressState::Idle);
+    }
+}
+
+fn update_progress_state(
+    mut evt_started: EventWriter<ProgressStarted>,
+    mut evt_completed: EventWriter<ProgressCompleted>,
+    mut evt_cancelled: EventWriter<ProgressCancelled>,
+    mut q_progress: Query<(
+        Entity,
+        &ProgressRatio,
+        &mut ProgressState,
+        Option<&mut ProgressTime>,
+    )>,
+) {
+    for (entity, ratio, mut state, time) in q_progress.iter_mut() {
+        let (active, forward) = match &time {
+            Some(time) => (time.active, time.direction == ProgressDirection::Forward),
+            None => (ratio.ratio > 0f32, true),
+        };
+        if *state == ProgressState::Idle && active {
+            *state = ProgressState::Running;
+            evt_started.send(ProgressStarted { entity });
+        }
+        match *state {
+            ProgressState::Running if forward && ratio.ratio >= 1f32 => {
+                *state = ProgressState::Completed;
+                if let Some(mut time) = time {
+                    time.active = false;
+                }
+                evt_completed.send(ProgressCompleted { entity });
+            }
+            ProgressState::Running if !active => {
+                *state = ProgressState::Idle;
+                evt_cancelled.send(ProgressCancelled { entity });
+            }
+            ProgressState::Completed if !active || ratio.ratio < 1f32 => {
+                *state = ProgressState::Idle;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_display(time: Res<Time>, mut q_display: Query<(&ProgressRatio, &mut ProgressDisplay)>) {
+    for (ratio, mut display) in q_display.iter_mut() {
+        let target = display.easing.apply(ratio.ratio);
+        display.value = if display.smoothing > 0f32 {
+            let value = display.value;
+            value + (target - value) * (1f32 - (-display.smoothing * time.delta_seconds()).exp())
+        } else {
+            target
+        };
+    }
+}
+
+pub(crate) fn shown_ratio(ratio: &ProgressRatio, display: Option<&ProgressDisplay>) -> f32 {
+    display.map_or(ratio.ratio, ProgressDisplay::value)
+}
+
+fn update_text(
+    q_data: Query<(
+        &ProgressRatio,
+        Option<&ProgressDisplay>,
+        Option<&ProgressTime>,
+    )>,
+    mut q_progress: Query<(&mut Text, &ProgressText)>,
+) {
+    for (mut text, progress_text) in q_progress.iter_mut() {
+        if let Ok((progress, display, time)) = q_data.get(progress_text.progress_entity) {
+            let remaining = time.map_or(0f32, ProgressTime::remaining_seconds);
+            let value = progress_text.format(shown_ratio(progress, display), remaining);
+            if let Some(section) = text.sections.get_mut(progress_text.section) {
+                section.value = value;
+            }
         }
     }
 }
 fn update_scale(
-    q_data: Query<&ProgressRatio>,
+    q_data: Query<(&ProgressRatio, Option<&ProgressDisplay>)>,
     mut q_progress: Query<(&mut Transform, &ProgressScale)>,
 ) {
     for (mut transform, scale) in q_progress.iter_mut() {
-        if let Ok(progress) = q_data.get_component::<ProgressRatio>(scale.progress_entity) {
-            transform.scale =
-                scale.scale_base + (scale.scale_mult * progress.ratio.clamp(0f32, 1f32));
+        if let Ok((progress, display)) = q_data.get(scale.progress_entity) {
+            transform.scale = scale.scale_base
+                + (scale.scale_mult * shown_ratio(progress, display).clamp(0f32, 1f32));
         }
     }
 }
@@ -0,0 +1,229 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    sprite::{Anchor, Mesh2dHandle},
+};
+
+use crate::{shown_ratio, ProgressDisplay, ProgressRatio};
+
+/// Fills a ring clockwise from the top, on an entity with a [`Mesh2dHandle`],
+/// e.g. around a button to hold.
+#[derive(Component)]
+pub struct ProgressRadial {
+    pub progress_entity: Entity,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    /// Number of quads for a full ring.
+    pub resolution: usize,
+    /// Ratio of the current mesh, so it's only rebuilt when it changes.
+    shown: Option<f32>,
+}
+
+impl ProgressRadial {
+    pub fn new(progress_entity: Entity, inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            progress_entity,
+            inner_radius,
+            outer_radius,
+            resolution: 64,
+            shown: None,
+        }
+    }
+
+    /// Mesh of the ring filled up to `ratio`.
+    pub fn mesh(&self, ratio: f32) -> Mesh {
+        let ratio = ratio.clamp(0f32, 1f32);
+        let steps = ((self.resolution as f32 * ratio).ceil() as usize).max(1);
+        let mut positions = Vec::with_capacity((steps + 1) * 2);
+        let mut uvs = Vec::with_capacity((steps + 1) * 2);
+        for i in 0..=steps {
+            let angle = FRAC_PI_2 - TAU * ratio * i as f32 / steps as f32;
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            for radius in [self.inner_radius, self.outer_radius] {
+                let p = direction * radius;
+                positions.push([p.x, p.y, 0f32]);
+                uvs.push([
+                    i as f32 / steps as f32,
+                    (radius == self.outer_radius) as u32 as f32,
+                ]);
+            }
+        }
+        let mut indices = Vec::with_capacity(steps * 6);
+        for i in 0..steps as u32 {
+            let (inner, outer) = (i * 2, i * 2 + 1);
+            indices.extend_from_slice(&[inner, inner + 2, outer, outer, inner + 2, outer + 2]);
+        }
+        let normals = vec![[0f32, 0f32, 1f32]; positions.len()];
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// Row of [`ProgressSegment`] ticks spread over `size`, spawned as children from the left.
+///
+/// The ticks are spawned again when `count` changes, e.g. from
+/// [`crate::ProgressBinding::with_max`].
+#[derive(Component)]
+pub struct ProgressSegments {
+    pub progress_entity: Entity,
+    pub count: usize,
+    pub size: Vec2,
+    /// Space between two ticks.
+    pub gap: f32,
+    pub filled: Color,
+    pub empty: Color,
+}
+
+/// One tick of a segmented bar: coloured `filled` once the progress reaches it.
+#[derive(Component)]
+pub struct ProgressSegment {
+    pub progress_entity: Entity,
+    pub index: usize,
+    pub count: usize,
+    pub filled: Color,
+    pub empty: Color,
+}
+
+/// Interpolates the colour of a [`Sprite`] or [`ColorMaterial`] along `gradient`,
+/// keys are `(ratio, colour)` sorted by ratio.
+#[derive(Component)]
+pub struct ProgressColor {
+    pub progress_entity: Entity,
+    pub gradient: Vec<(f32, Color)>,
+}
+
+impl ProgressColor {
+    pub fn sample(&self, ratio: f32) -> Color {
+        let (first, last) = match (self.gradient.first(), self.gradient.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::WHITE,
+        };
+        if ratio <= first.0 {
+            return first.1;
+        }
+        for window in self.gradient.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            if ratio <= end.0 {
+                let span = end.0 - start.0;
+                let t = if span > 0f32 {
+                    (ratio - start.0) / span
+                } else {
+                    1f32
+                };
+                let (a, b) = (Vec4::from(start.1), Vec4::from(end.1));
+                return Color::from(a + (b - a) * t);
+            }
+        }
+        last.1
+    }
+}
+
+pub(crate) fn update_radial(
+    mut meshes: ResMut<Assets<Mesh>>,
+    q_data: Query<(&ProgressRatio, Option<&ProgressDisplay>)>,
+    mut q_radial: Query<(&mut Mesh2dHandle, &mut ProgressRadial, Option<&Visibility>)>,
+) {
+    for (mut handle, mut radial, visibility) in q_radial.iter_mut() {
+        if visibility.is_some_and(|v| !v.is_visible) {
+            continue;
+        }
+        if let Ok((progress, display)) = q_data.get(radial.progress_entity) {
+            let ratio = shown_ratio(progress, display);
+            if radial.shown == Some(ratio) && meshes.contains(&handle.0) {
+                continue;
+            }
+            radial.shown = Some(ratio);
+            let mesh = radial.mesh(ratio);
+            match meshes.get_mut(&handle.0) {
+                Some(existing) => *existing = mesh,
+                None => handle.0 = meshes.add(mesh),
+            }
+        }
+    }
+}
+
+pub(crate) fn spawn_segments(
+    mut commands: Commands,
+    q_segments: Query<(Entity, &ProgressSegments), Changed<ProgressSegments>>,
+) {
+    for (entity, segments) in q_segments.iter() {
+        let count = segments.count.max(1) as f32;
+        let width = ((segments.size.x - segments.gap * (count - 1f32)) / count).max(1f32);
+        let ticks: Vec<Entity> = (0..segments.count)
+            .map(|index| {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: segments.empty,
+                            custom_size: Some(Vec2::new(width, segments.size.y)),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(Vec3::new(
+                            index as f32 * (width + segments.gap),
+                            0f32,
+                            0f32,
+                        )),
+                        ..default()
+                    })
+                    .insert(ProgressSegment {
+                        progress_entity: segments.progress_entity,
+                        index,
+                        count: segments.count,
+                        filled: segments.filled,
+                        empty: segments.empty,
+                    })
+                    .id()
+            })
+            .collect();
+        let mut container = commands.entity(entity);
+        container.despawn_descendants();
+        container.push_children(&ticks);
+    }
+}
+
+pub(crate) fn update_segments(
+    q_data: Query<(&ProgressRatio, Option<&ProgressDisplay>)>,
+    mut q_segments: Query<(&mut Sprite, &ProgressSegment)>,
+) {
+    for (mut sprite, segment) in q_segments.iter_mut() {
+        if let Ok((progress, display)) = q_data.get(segment.progress_entity) {
+            let reached = shown_ratio(progress, display) * segment.count as f32;
+            sprite.color = if reached >= (segment.index + 1) as f32 - 0.001f32 {
+                segment.filled
+            } else {
+                segment.empty
+            };
+        }
+    }
+}
+
+pub(crate) fn update_color(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q_data: Query<(&ProgressRatio, Option<&ProgressDisplay>)>,
+    mut q_sprites: Query<(&mut Sprite, &ProgressColor)>,
+    q_materials: Query<(&Handle<ColorMaterial>, &ProgressColor)>,
+) {
+    let sample = |color: &ProgressColor| {
+        q_data
+            .get(color.progress_entity)
+            .ok()
+            .map(|(progress, display)| color.sample(shown_ratio(progress, display)))
+    };
+    for (mut sprite, color) in q_sprites.iter_mut() {
+        if let Some(sampled) = sample(color) {
+            sprite.color = sampled;
+        }
+    }
+    for (material, color) in q_materials.iter() {
+        if let (Some(sampled), Some(material)) = (sample(color), materials.get_mut(material)) {
+            material.color = sampled;
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraEffect>()
+            .init_resource::<CameraEffectsSettings>()
+            .add_startup_system(setup_flash)
+            .add_system(receive_camera_effects)
+            .add_system(update_camera_effects.after(receive_camera_effects))
+            .add_system(update_flash.after(update_camera_effects));
+    }
+}
+
+/// Strong feedback applied to the camera, usually sent from a [`crate::FeedbackStyle`].
+#[derive(Debug, Clone, Default)]
+pub struct CameraEffect {
+    /// Added to the current trauma, from 0 to 1. Shake is proportional to trauma squared.
+    pub trauma: f32,
+    /// Fraction of the camera scale to zoom in by, decaying back to 0.
+    pub zoom_punch: f32,
+    /// Full screen colour flash, its alpha is the flash strength.
+    pub flash: Option<Color>,
+}
+
+impl CameraEffect {
+    pub fn is_none(&self) -> bool {
+        self.trauma <= 0f32 && self.zoom_punch <= 0f32 && self.flash.is_none()
+    }
+}
+
+pub struct CameraEffectsSettings {
+    /// Multiplier applied to every effect.
+    pub intensity: f32,
+    /// Disables shake and zoom, and dims flashes.
+    pub reduce_motion: bool,
+    /// Maximum camera offset at full trauma, in world units.
+    pub max_offset: f32,
+    /// Maximum camera roll at full trauma, in radians.
+    pub max_angle: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Zoom punch lost per second.
+    pub zoom_decay: f32,
+    /// Flash alpha lost per second.
+    pub flash_decay: f32,
+}
+
+impl Default for CameraEffectsSettings {
+    fn default() -> Self {
+        Self {
+            intensity: 1f32,
+            reduce_motion: false,
+            max_offset: 60f32,
+            max_angle: 0.05f32,
+            trauma_decay: 1.5f32,
+            zoom_decay: 0.5f32,
+            flash_decay: 3f32,
+        }
+    }
+}
+
+/// Put on the camera to receive [`CameraEffect`]s.
+#[derive(Component)]
+pub struct CameraEffects {
+    pub trauma: f32,
+    pub zoom: f32,
+    base_scale: f32,
+    base_translation: Vec3,
+}
+
+impl CameraEffects {
+    pub fn new(base_scale: f32, base_translation: Vec3) -> Self {
+        Self {
+            trauma: 0f32,
+            zoom: 0f32,
+            base_scale,
+            base_translation,
+        }
+    }
+}
+
+#[derive(Component)]
+struct CameraFlash;
+
+fn setup_flash(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::NONE,
+                custom_size: Some(Vec2::splat(10000f32)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0f32, 0f32, 900f32)),
+            ..default()
+        })
+        .insert(CameraFlash);
+}
+
+fn receive_camera_effects(
+    settings: Res<CameraEffectsSettings>,
+    mut effects: EventReader<CameraEffect>,
+    mut q_camera: Query<&mut CameraEffects>,
+    mut q_flash: Query<&mut Sprite, With<CameraFlash>>,
+) {
+    for effect in effects.iter() {
+        if !settings.reduce_motion {
+            for mut camera in q_camera.iter_mut() {
+                camera.trauma = (camera.trauma + effect.trauma * settings.intensity).min(1f32);
+                camera.zoom = (camera.zoom + effect.zoom_punch * settings.intensity).min(0.5f32);
+            }
+        }
+        if let Some(mut color) = effect.flash {
+            let mut alpha = color.a() * settings.intensity;
+            if settings.reduce_motion {
+                alpha *= 0.3f32;
+            }
+            color.set_a(alpha.min(1f32));
+            for mut sprite in q_flash.iter_mut() {
+                if sprite.color.a() <= color.a() {
+                    sprite.color = color;
+                }
+            }
+        }
+    }
+}
+
+fn update_camera_effects(
+    time: Res<Time>,
+    settings: Res<CameraEffectsSettings>,
+    mut q_camera: Query<(
+        &mut CameraEffects,
+        &mut Transform,
+        &mut OrthographicProjection,
+    )>,
+) {
+    let t = time.seconds_since_startup() as f32;
+    let dt = time.delta_seconds();
+    for (mut effects, mut transform, mut projection) in q_camera.iter_mut() {
+        if effects.trauma <= 0f32 && effects.zoom <= 0f32 {
+            continue;
+        }
+        effects.trauma = (effects.trauma - settings.trauma_decay * dt).max(0f32);
+        effects.zoom = (effects.zoom - settings.zoom_decay * dt).max(0f32);
+
+        // Cheap smooth noise, different frequencies per axis so they don't line up.
+        let shake = effects.trauma * effects.trauma;
+        let offset =
+            Vec2::new((t * 41f32).sin(), (t * 37f32 + 1.3f32).sin()) * settings.max_offset * shake;
+        let angle = (t * 29f32 + 2.1f32).sin() * settings.max_angle * shake;
+        transform.translation = effects.base_translation + offset.extend(0f32);
+        transform.rotation = Quat::from_rotation_z(angle);
+        projection.scale = effects.base_scale * (1f32 - effects.zoom);
+    }
+}
+
+fn update_flash(
+    time: Res<Time>,
+    settings: Res<CameraEffectsSettings>,
+    q_camera: Query<&Transform, (With<CameraEffects>, Without<CameraFlash>)>,
+    mut q_flash: Query<(&mut Sprite, &mut Transform), With<CameraFlash>>,
+) {
+    let camera_position = q_camera.get_single().map(|t| t.translation.xy());
+    for (mut sprite, mut transform) in q_flash.iter_mut() {
+        let alpha = sprite.color.a();
+        if alpha > 0f32 {
+            sprite
+                .color
+                .set_a((alpha - settings.flash_decay * time.delta_seconds()).max(0f32));
+        }
+        if let Ok(position) = camera_position {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{ActiveCamera, Camera2d, RenderTarget},
+        render_asset::RenderAssets,
+        render_resource::{
+            BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+            ImageDataLayout, MapMode, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::BevyDefault,
+        RenderApp, RenderStage,
+    },
+};
+
+/// Saves the frames requested with [`CaptureFrame`] as PNG files.
+///
+/// The 2d camera is redirected to an offscreen image for one frame, which is then copied back
+/// from the GPU, so it doesn't depend on reading the window surface, nor on having a window.
+pub struct FrameCapturePlugin;
+
+impl Plugin for FrameCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CaptureFrame>()
+            .add_event::<FrameCaptured>()
+            .init_resource::<CaptureSettings>()
+            .init_resource::<PendingCapture>()
+            .add_system(advance_capture)
+            .add_system(start_capture.after(advance_capture));
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_system_to_stage(RenderStage::Extract, extract_capture)
+                .add_system_to_stage(RenderStage::Cleanup, save_capture);
+        }
+    }
+}
+
+/// Request a PNG of the next rendered frame.
+#[derive(Debug, Clone)]
+pub struct CaptureFrame {
+    pub path: PathBuf,
+}
+
+/// Sent once the requested frame has been rendered, so feedback can be shown without being part
+/// of the capture.
+#[derive(Debug, Clone)]
+pub struct FrameCaptured {
+    pub path: PathBuf,
+}
+
+/// Size of captures when there is no primary window to match, e.g. when running headless.
+pub struct CaptureSettings {
+    pub size: UVec2,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            size: UVec2::new(1280, 1024),
+        }
+    }
+}
+
+enum CaptureStage {
+    /// The offscreen image was just created, it has to reach the GPU first.
+    Preparing,
+    /// The camera renders into the offscreen image this frame, and goes back to its previous
+    /// target after.
+    Rendering(RenderTarget),
+}
+
+#[derive(Default)]
+struct PendingCapture {
+    current: Option<(CaptureStage, Handle<Image>, PathBuf)>,
+    queued: Vec<CaptureFrame>,
+}
+
+/// Render world copy of the capture rendered this frame.
+struct ExtractedCapture {
+    image: Handle<Image>,
+    path: PathBuf,
+}
+
+fn start_capture(
+    windows: Res<Windows>,
+    settings: Res<CaptureSettings>,
+    mut images: ResMut<Assets<Image>>,
+    mut pending: ResMut<PendingCapture>,
+    mut requests: EventReader<CaptureFrame>,
+) {
+    pending.queued.extend(requests.iter().cloned());
+    if pending.current.is_some() || pending.queued.is_empty() {
+        return;
+    }
+    let size = windows
+        .get_primary()
+        .map(|window| UVec2::new(window.physical_width(), window.physical_height()))
+        .unwrap_or(settings.size);
+    let request = pending.queued.remove(0);
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::bevy_default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    pending.current = Some((CaptureStage::Preparing, images.add(image), request.path));
+}
+
+fn advance_capture(
+    active_camera: Res<ActiveCamera<Camera2d>>,
+    mut pending: ResMut<PendingCapture>,
+    mut captured: EventWriter<FrameCaptured>,
+    mut q_camera: Query<&mut Camera>,
+) {
+    let camera_entity = match active_camera.get() {
+        Some(entity) => entity,
+        None => return,
+    };
+    let mut camera = match q_camera.get_mut(camera_entity) {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    match pending.current.take() {
+        Some((CaptureStage::Preparing, image, path)) => {
+            let previous =
+                std::mem::replace(&mut camera.target, RenderTarget::Image(image.clone()));
+            pending.current = Some((CaptureStage::Rendering(previous), image, path));
+        }
+        Some((CaptureStage::Rendering(previous), _, path)) => {
+            camera.target = previous;
+            captured.send(FrameCaptured { path });
+        }
+        None => {}
+    }
+}
+
+fn extract_capture(mut commands: Commands, pending: Res<PendingCapture>) {
+    if let Some((CaptureStage::Rendering(_), image, path)) = &pending.current {
+        commands.insert_resource(ExtractedCapture {
+            image: image.clone(),
+            path: path.clone(),
+        });
+    }
+}
+
+fn save_capture(
+    mut commands: Commands,
+    capture: Option<Res<ExtractedCapture>>,
+    gpu_images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let capture = match capture {
+        Some(capture) => capture,
+        None => return,
+    };
+    commands.remove_resource::<ExtractedCapture>();
+    let gpu_image = match gpu_images.get(&capture.image) {
+        Some(gpu_image) => gpu_image,
+        None => {
+            warn!("Capture image was not ready, skipping {:?}", capture.path);
+            return;
+        }
+    };
+    let width = gpu_image.size.width as u32;
+    let height = gpu_image.size.height as u32;
+    let row_bytes = width as usize * 4;
+    let padded_row_bytes = RenderDevice::align_copy_bytes_per_row(row_bytes);
+
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("frame_capture_buffer"),
+        size: (padded_row_bytes * height as usize) as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("frame_capture_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        gpu_image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_row_bytes as u32),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    render_queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    render_device.map_buffer(&slice, MapMode::Read);
+    let bgra = gpu_image.texture_format == TextureFormat::Bgra8UnormSrgb
+        || gpu_image.texture_format == TextureFormat::Bgra8Unorm;
+    let result = save_pixels(
+        &capture.path,
+        &slice.get_mapped_range(),
+        UVec2::new(width, height),
+        padded_row_bytes,
+        bgra,
+    );
+    buffer.unmap();
+    match result {
+        Ok(()) => info!("Captured frame to {:?}", capture.path),
+        Err(e) => error!("Could not save capture {:?}: {}", capture.path, e),
+    }
+}
+
+/// Writes 8 bits per channel pixels copied from the GPU as a PNG. Rows of `data` are
+/// `padded_row_bytes` long, as required for buffer copies.
+fn save_pixels(
+    path: &Path,
+    data: &[u8],
+    size: UVec2,
+    padded_row_bytes: usize,
+    bgra: bool,
+) -> image::ImageResult<()> {
+    let row_bytes = size.x as usize * 4;
+    let mut pixels = Vec::with_capacity(row_bytes * size.y as usize);
+    for row in data.chunks(padded_row_bytes).take(size.y as usize) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    if bgra {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    image::save_buffer(path, &pixels, size.x, size.y, image::ColorType::Rgba8)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, ecs::event::Events};
+
+    use super::*;
+
+    fn test_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("arcade_capture_{}", std::process::id()))
+    }
+
+    #[test]
+    fn saves_padded_bgra_pixels() {
+        let path = test_dir().join("nested").join("capture.png");
+        // 2x2 image, rows padded to 12 bytes.
+        let data = [
+            255, 0, 0, 255, 0, 255, 0, 255, 9, 9, 9, 9, //
+            0, 0, 255, 255, 255, 255, 255, 255, 9, 9, 9, 9,
+        ];
+        save_pixels(&path, &data, UVec2::new(2, 2), 12, true).unwrap();
+        let saved = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(saved.dimensions(), (2, 2));
+        assert_eq!(saved.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(saved.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(saved.get_pixel(0, 1).0, [255, 0, 0, 255]);
+        assert_eq!(saved.get_pixel(1, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn starts_headless_capture_at_configured_size() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .init_resource::<Windows>()
+            .insert_resource(CaptureSettings {
+                size: UVec2::new(64, 32),
+            })
+            .init_resource::<PendingCapture>()
+            .add_event::<CaptureFrame>()
+            .add_system(start_capture);
+        app.world
+            .resource_mut::<Events<CaptureFrame>>()
+            .send(CaptureFrame {
+                path: test_dir().join("headless.png"),
+            });
+        app.update();
+
+        let pending = app.world.resource::<PendingCapture>();
+        let image = match &pending.current {
+            Some((CaptureStage::Preparing, image, _)) => image.clone(),
+            _ => panic!("capture should be preparing"),
+        };
+        let size = app
+            .world
+            .resource::<Assets<Image>>()
+            .get(&image)
+            .unwrap()
+            .size();
+        assert_eq!(size, Vec2::new(64f32, 32f32));
+    }
+}
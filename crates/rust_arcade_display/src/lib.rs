@@ -1,8 +1,19 @@
+pub mod camera_effects;
+pub mod capture;
+pub mod popup_text;
+pub mod reaction_queue;
+
 use std::collections::HashMap;
 
 use bevy::{math::Vec3Swizzles, prelude::*};
+use camera_effects::{CameraEffect, CameraEffects, CameraEffectsPlugin};
+use capture::FrameCapturePlugin;
+use popup_text::PopupTextPlugin;
+use reaction_queue::ReactionQueue;
 
-use particles::{DestroyAfter, ParticleExplosion, ParticlesPlugin, Velocity};
+use particles::{
+    preset::EmitterPreset, DestroyAfter, ParticleExplosion, ParticlesPlugin, Velocity,
+};
 use rust_arcade::bevy_rust_arcade::ArcadeInput;
 
 #[derive(Default)]
@@ -15,9 +26,16 @@ impl Plugin for ArcadeDisplayPlugin {
                 .with_system(load_images)
                 .with_system(load_sounds),
         )
+        // Before other startup systems, which can spawn text with its font.
+        .add_startup_system_to_stage(StartupStage::PreStartup, load_theme)
         .add_startup_system_to_stage(StartupStage::PostStartup, setup)
         .add_plugin(ParticlesPlugin)
+        .add_plugin(CameraEffectsPlugin)
+        .add_plugin(PopupTextPlugin)
+        .add_plugin(FrameCapturePlugin)
         .add_event::<InputReaction>()
+        .init_resource::<FeedbackStyles>()
+        .init_resource::<ReactionQueue>()
         .add_system(handle_reaction_events);
     }
 }
@@ -33,6 +51,19 @@ struct ButtonSoundsFr {
     pub sounds: HashMap<ArcadeInput, EqAudioSource>,
 }
 
+/// Fonts and text styles shared by the display and the games.
+pub struct DisplayTheme {
+    pub font: Handle<Font>,
+    pub popup_font_size: f32,
+    pub popup_color: Color,
+    /// Offset from the button where popups appear.
+    pub popup_offset: Vec2,
+    /// Upward speed of popups, in world units per second.
+    pub popup_rise_speed: f32,
+    /// Seconds before a popup is fully faded and removed.
+    pub popup_lifetime: f32,
+}
+
 #[derive(Eq)]
 struct EqAudioSource(pub Handle<AudioSource>);
 
@@ -48,7 +79,7 @@ impl From<Handle<AudioSource>> for EqAudioSource {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FeedbackType {
     Good,
     Bad,
@@ -57,6 +88,142 @@ pub enum FeedbackType {
     Cheat,
     Menu,
     Fun,
+    /// Game specific feedback, its look is registered in [`FeedbackStyles`].
+    Custom(&'static str),
+}
+
+/// Colour used for the overlay and particles of a feedback.
+#[derive(Debug, Clone)]
+pub enum FeedbackColor {
+    Fixed(Color),
+    /// Hue cycling with time.
+    Rainbow,
+}
+
+/// Sound played when a feedback is shown.
+#[derive(Debug, Clone)]
+pub enum FeedbackSound {
+    Silent,
+    /// The sound of the pressed key.
+    Key,
+    /// The alternate ("-fr") sound of the pressed key.
+    KeyAlternate,
+    Custom(Handle<AudioSource>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedbackStyle {
+    pub color: FeedbackColor,
+    /// z of the overlay sprite spawned over the button.
+    pub layer: f32,
+    pub sound: FeedbackSound,
+    /// Particles burst from the button, if any.
+    pub particles: Option<Handle<EmitterPreset>>,
+    pub camera: CameraEffect,
+    /// Higher priority reactions replace pending lower ones on the same key.
+    pub priority: i32,
+}
+
+impl FeedbackStyle {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color: FeedbackColor::Fixed(color),
+            layer: 20f32,
+            sound: FeedbackSound::Key,
+            particles: Some(Handle::default()),
+            camera: CameraEffect::default(),
+            priority: 1,
+        }
+    }
+
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_sound(mut self, sound: FeedbackSound) -> Self {
+        self.sound = sound;
+        self
+    }
+
+    pub fn with_particles(mut self, particles: Option<Handle<EmitterPreset>>) -> Self {
+        self.particles = particles;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: CameraEffect) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Look of each [`FeedbackType`], games can register their own [`FeedbackType::Custom`] kinds.
+pub struct FeedbackStyles {
+    pub styles: HashMap<FeedbackType, FeedbackStyle>,
+}
+
+impl Default for FeedbackStyles {
+    fn default() -> Self {
+        Self {
+            styles: HashMap::from([
+                (FeedbackType::Good, FeedbackStyle::new(Color::GREEN)),
+                (
+                    FeedbackType::Bad,
+                    FeedbackStyle::new(Color::RED)
+                        .with_sound(FeedbackSound::KeyAlternate)
+                        .with_priority(3)
+                        .with_camera(CameraEffect {
+                            trauma: 0.6f32,
+                            flash: Some(Color::rgba(1f32, 0f32, 0f32, 0.25f32)),
+                            ..default()
+                        }),
+                ),
+                (
+                    FeedbackType::Last,
+                    FeedbackStyle::new(Color::YELLOW)
+                        .with_priority(2)
+                        .with_camera(CameraEffect {
+                            zoom_punch: 0.08f32,
+                            ..default()
+                        }),
+                ),
+                (FeedbackType::New, FeedbackStyle::new(Color::BLUE)),
+                (
+                    FeedbackType::Cheat,
+                    FeedbackStyle::new(Color::GRAY)
+                        .with_layer(19f32)
+                        .with_sound(FeedbackSound::Silent)
+                        .with_priority(0),
+                ),
+                (FeedbackType::Menu, FeedbackStyle::new(Color::FUCHSIA)),
+                (
+                    FeedbackType::Fun,
+                    FeedbackStyle {
+                        color: FeedbackColor::Rainbow,
+                        ..FeedbackStyle::new(Color::WHITE)
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+impl FeedbackStyles {
+    pub fn register(&mut self, feedback: FeedbackType, style: FeedbackStyle) {
+        self.styles.insert(feedback, style);
+    }
+
+    pub fn get(&self, feedback: &FeedbackType) -> &FeedbackStyle {
+        self.styles.get(feedback).unwrap_or_else(|| {
+            warn!("No style registered for {:?}", feedback);
+            &self.styles[&FeedbackType::Cheat]
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -92,6 +259,17 @@ fn load_images(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(button_images);
 }
 
+fn load_theme(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DisplayTheme {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        popup_font_size: 70f32,
+        popup_color: Color::WHITE,
+        popup_offset: Vec2::new(0f32, 100f32),
+        popup_rise_speed: 150f32,
+        popup_lifetime: 0.8f32,
+    });
+}
+
 fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
     let sounds_map_name = [
         (ArcadeInput::JoyUp, "up"),
@@ -126,11 +304,14 @@ fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(sounds_fr);
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, images: Res<ButtonImages>) {
+fn setup(mut commands: Commands, theme: Res<DisplayTheme>, images: Res<ButtonImages>) {
     let scale = 1.5f32;
     let mut cam_bundle = OrthographicCameraBundle::new_2d();
     cam_bundle.orthographic_projection.scale = scale;
-    commands.spawn_bundle(cam_bundle);
+    let cam_translation = cam_bundle.transform.translation;
+    commands
+        .spawn_bundle(cam_bundle)
+        .insert(CameraEffects::new(scale, cam_translation));
     let width = 1280f32 * scale;
     let height = 1024f32 * scale;
 
@@ -186,9 +367,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, images: Res<But
         }
     }
 
-    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     let text_style = TextStyle {
-        font,
+        font: theme.font.clone(),
         font_size: 60.0,
         color: Color::BLACK,
     };
@@ -258,48 +438,46 @@ fn handle_reaction_events(
     sounds: Res<ButtonSounds>,
     sounds_fr: Res<ButtonSoundsFr>,
     audio: Res<Audio>,
+    styles: Res<FeedbackStyles>,
     time: Res<Time>,
+    mut queue: ResMut<ReactionQueue>,
     mut reactions: EventReader<InputReaction>,
     mut particles: EventWriter<ParticleExplosion>,
+    mut camera_effects: EventWriter<CameraEffect>,
     q_reactables: Query<(&Transform, &Reactable)>,
 ) {
     for ev in reactions.iter() {
-        let (color, layer) = match ev.feedback {
-            FeedbackType::Bad => (Color::RED, 20f32),
-            FeedbackType::Good => (Color::GREEN, 20f32),
-            FeedbackType::Last => (Color::YELLOW, 20f32),
-            FeedbackType::New => (Color::BLUE, 20f32),
-            FeedbackType::Cheat => (Color::GRAY, 19f32),
-            FeedbackType::Menu => (Color::FUCHSIA, 20f32),
-            FeedbackType::Fun => (
-                Color::Hsla {
-                    hue: time.seconds_since_startup() as f32 * 30f32,
-                    saturation: 1f32,
-                    lightness: 0.5f32,
-                    alpha: 1f32,
-                },
-                20f32,
-            ),
+        let priority = styles.get(&ev.feedback).priority;
+        queue.push(ev.key.clone(), ev.feedback.clone(), priority);
+    }
+    for (key, feedback) in queue.pop_ready(time.seconds_since_startup()) {
+        let style = styles.get(&feedback);
+        let color = match style.color {
+            FeedbackColor::Fixed(color) => color,
+            FeedbackColor::Rainbow => Color::Hsla {
+                hue: time.seconds_since_startup() as f32 * 30f32,
+                saturation: 1f32,
+                lightness: 0.5f32,
+                alpha: 1f32,
+            },
+        };
+        let layer = style.layer;
+        let sound = match &style.sound {
+            FeedbackSound::Silent => None,
+            FeedbackSound::Key => Some(sounds.sounds[&key].0.clone()),
+            FeedbackSound::KeyAlternate => Some(sounds_fr.sounds[&key].0.clone()),
+            FeedbackSound::Custom(handle) => Some(handle.clone()),
         };
-        if ev.feedback != FeedbackType::Cheat {
-            audio.play(
-                if ev.feedback == FeedbackType::Bad {
-                    &sounds_fr.sounds
-                } else {
-                    &sounds.sounds
-                }[&ev.key]
-                    .0
-                    .clone(),
-            );
+        if let Some(sound) = sound {
+            audio.play(sound);
+        }
+        if !style.camera.is_none() {
+            camera_effects.send(style.camera.clone());
         }
         for (t, r) in q_reactables.iter() {
-            if r.key != ev.key {
+            if r.key != key {
                 continue;
             }
-            let particle = ParticleExplosion {
-                location: t.translation.xy(),
-                color,
-            };
             let image_handle = match r.key {
                 ArcadeInput::JoyRight
                 | ArcadeInput::JoyDown
@@ -315,10 +493,12 @@ fn handle_reaction_events(
                         .with_rotation(t.rotation),
                     ..default()
                 })
-                .insert(DestroyAfter::new(
-                    time.seconds_since_startup() as f32 + 0.5f32,
-                ));
-            particles.send(particle);
+                .insert_bundle(DestroyAfter::new(0.5f32));
+            if let Some(preset) = &style.particles {
+                particles.send(
+                    ParticleExplosion::new(t.translation.xy(), color).with_preset(preset.clone()),
+                );
+            }
         }
     }
 }
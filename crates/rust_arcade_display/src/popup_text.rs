@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use rust_arcade::bevy_rust_arcade::ArcadeInput;
+
+use crate::{DisplayTheme, Reactable};
+
+pub struct PopupTextPlugin;
+
+impl Plugin for PopupTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PopupText>()
+            .add_system(spawn_popups)
+            .add_system(update_popups);
+    }
+}
+
+/// Shows a short text ("PERFECT", "+1"...) over the [`Reactable`] of `key`, rising and fading.
+#[derive(Debug, Clone)]
+pub struct PopupText {
+    pub key: ArcadeInput,
+    pub text: String,
+    /// Defaults to [`DisplayTheme::popup_color`].
+    pub color: Option<Color>,
+}
+
+impl PopupText {
+    pub fn new(key: ArcadeInput, text: impl Into<String>) -> Self {
+        Self {
+            key,
+            text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+#[derive(Component)]
+struct Popup {
+    elapsed: f32,
+    color: Color,
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    theme: Res<DisplayTheme>,
+    mut popups: EventReader<PopupText>,
+    q_reactables: Query<(&Transform, &Reactable)>,
+) {
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    for popup in popups.iter() {
+        let color = popup.color.unwrap_or(theme.popup_color);
+        for (t, r) in q_reactables.iter() {
+            if r.key != popup.key {
+                continue;
+            }
+            let position = t.translation.truncate() + theme.popup_offset;
+            commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        popup.text.clone(),
+                        TextStyle {
+                            font: theme.font.clone(),
+                            font_size: theme.popup_font_size,
+                            color,
+                        },
+                        text_alignment,
+                    ),
+                    transform: Transform::from_translation(position.extend(600f32)),
+                    ..default()
+                })
+                .insert(Popup {
+                    elapsed: 0f32,
+                    color,
+                });
+        }
+    }
+}
+
+fn update_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    theme: Res<DisplayTheme>,
+    mut q_popups: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+) {
+    for (e, mut popup, mut transform, mut text) in q_popups.iter_mut() {
+        popup.elapsed += time.delta_seconds();
+        if theme.popup_lifetime <= popup.elapsed {
+            commands.entity(e).despawn();
+            continue;
+        }
+        transform.translation.y += theme.popup_rise_speed * time.delta_seconds();
+        let ratio = popup.elapsed / theme.popup_lifetime;
+        let mut color = popup.color;
+        color.set_a(popup.color.a() * (1f32 - ratio * ratio));
+        for section in text.sections.iter_mut() {
+            section.style.color = color;
+        }
+    }
+}
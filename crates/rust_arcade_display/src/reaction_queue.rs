@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use rust_arcade::bevy_rust_arcade::ArcadeInput;
+
+use crate::FeedbackType;
+
+/// Pending reactions for each key, so a burst of [`crate::InputReaction`]s doesn't spawn
+/// overlays on top of each other.
+///
+/// Within one key, a reaction supersedes pending ones with a lower priority and is dropped if a
+/// higher priority one is pending. Identical pending reactions are merged. A key shows at most
+/// one reaction per `min_interval`, unless the new one has a higher priority than the last shown.
+pub struct ReactionQueue {
+    /// Seconds between two reactions shown on the same key.
+    pub min_interval: f64,
+    keys: HashMap<ArcadeInput, KeyQueue>,
+}
+
+#[derive(Default)]
+struct KeyQueue {
+    pending: Vec<(FeedbackType, i32)>,
+    last_shown: Option<(f64, i32)>,
+}
+
+impl Default for ReactionQueue {
+    fn default() -> Self {
+        Self {
+            min_interval: 0.15f64,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl ReactionQueue {
+    pub fn push(&mut self, key: ArcadeInput, feedback: FeedbackType, priority: i32) {
+        let queue = self.keys.entry(key).or_default();
+        if queue
+            .pending
+            .iter()
+            .any(|(f, p)| *p > priority || *f == feedback)
+        {
+            return;
+        }
+        queue.pending.retain(|(_, p)| *p >= priority);
+        queue.pending.push((feedback, priority));
+    }
+
+    /// Removes and returns the reactions to show now.
+    pub fn pop_ready(&mut self, now: f64) -> Vec<(ArcadeInput, FeedbackType)> {
+        let mut ready = Vec::new();
+        for (key, queue) in self.keys.iter_mut() {
+            if queue.pending.is_empty() {
+                continue;
+            }
+            let (feedback, priority) = &queue.pending[0];
+            let can_show = match queue.last_shown {
+                None => true,
+                Some((time, last_priority)) => {
+                    *priority > last_priority || self.min_interval <= now - time
+                }
+            };
+            if can_show {
+                queue.last_shown = Some((now, *priority));
+                ready.push((key.clone(), feedback.clone()));
+                queue.pending.remove(0);
+            }
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: ArcadeInput = ArcadeInput::ButtonTop1;
+
+    #[test]
+    fn higher_priority_supersedes_pending() {
+        let mut queue = ReactionQueue::default();
+        queue.push(KEY, FeedbackType::Cheat, 0);
+        queue.push(KEY, FeedbackType::Bad, 2);
+        queue.push(KEY, FeedbackType::Good, 1);
+        assert_eq!(queue.pop_ready(0f64), vec![(KEY, FeedbackType::Bad)]);
+        assert_eq!(queue.pop_ready(1f64), vec![]);
+    }
+
+    #[test]
+    fn duplicate_feedback_is_merged() {
+        let mut queue = ReactionQueue::default();
+        queue.push(KEY, FeedbackType::Good, 1);
+        queue.push(KEY, FeedbackType::Good, 1);
+        assert_eq!(queue.pop_ready(0f64), vec![(KEY, FeedbackType::Good)]);
+        assert_eq!(queue.pop_ready(1f64), vec![]);
+    }
+
+    #[test]
+    fn lower_priority_waits_for_min_interval() {
+        let mut queue = ReactionQueue::default();
+        queue.push(KEY, FeedbackType::Good, 1);
+        assert_eq!(queue.pop_ready(0f64), vec![(KEY, FeedbackType::Good)]);
+
+        queue.push(KEY, FeedbackType::Menu, 0);
+        assert_eq!(queue.pop_ready(0.1f64), vec![]);
+        assert_eq!(queue.pop_ready(0.2f64), vec![(KEY, FeedbackType::Menu)]);
+
+        queue.push(KEY, FeedbackType::Bad, 2);
+        assert_eq!(queue.pop_ready(0.25f64), vec![(KEY, FeedbackType::Bad)]);
+    }
+}
@@ -1,36 +1,34 @@
+pub mod simon_capture;
+pub mod simon_classic;
+pub mod simon_game;
+pub mod simon_menu;
+pub mod simon_profile;
 pub mod simon_progress;
 pub mod simon_reset;
-
-use std::{
-    fs::{self, OpenOptions},
-    io::{BufReader, Write},
-    path::Path,
-};
+pub mod simon_save;
+pub mod simon_state;
 
 use bevy::{
-    app::AppExit,
     prelude::*,
     window::{PresentMode, WindowMode},
 };
+use particles::emitter::ParticleEmitter;
 use progress::ProgressPlugin;
 use rust_arcade::fake_arcade::KeyToArcade;
 use rust_arcade::{
     bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent, RustArcadePlugin},
     fake_arcade,
 };
-use rust_arcade_display::{ArcadeDisplayPlugin, InputReaction};
+use rust_arcade_display::{popup_text::PopupText, ArcadeDisplayPlugin, InputReaction, Reactable};
+use simon_capture::{CaptureCombo, SimonCapturePlugin};
+use simon_classic::{ClassicPlayback, SimonClassicPlugin};
+use simon_game::{Outcome, SimonGame, SimonMode};
+use simon_menu::{GameEnded, SimonMenuPlugin, StartGame};
+use simon_profile::SimonProfilePlugin;
 use simon_progress::SimonProgressPlugin;
-use simon_reset::{ResetState, SimonResetPlugin};
-
-#[derive(Default)]
-struct UserSequence {
-    sequence: Vec<ArcadeInput>,
-}
-
-#[derive(Default)]
-struct UserProgress {
-    index: usize,
-}
+use simon_reset::SimonResetPlugin;
+use simon_save::{Autosave, SimonSave, SimonSavePlugin};
+use simon_state::{SimonState, SimonStatePlugin, PAUSE_BUTTON};
 
 struct ShowNextPlay {
     pub next_play: f32,
@@ -42,7 +40,15 @@ enum CheatState {
     ShowNextPlay(ShowNextPlay),
 }
 
-struct SequenceFileToLoad(pub Option<String>);
+/// What happened in the game after `input` was pressed.
+pub struct GameOutcome {
+    pub input: ArcadeInput,
+    pub outcome: Outcome,
+}
+
+/// Sparkles around the next button to play while cheating.
+#[derive(Component)]
+struct CheatSparkle;
 
 fn main() {
     App::new()
@@ -56,65 +62,102 @@ fn main() {
         })
         .insert_resource(CheatState::Disabled)
         .add_plugins(DefaultPlugins)
+        .add_plugin(SimonStatePlugin)
         .add_plugin(ArcadeDisplayPlugin)
         .add_plugin(RustArcadePlugin)
         .add_plugin(ProgressPlugin)
         .add_plugin(SimonProgressPlugin)
         .add_plugin(SimonResetPlugin)
+        .add_plugin(SimonCapturePlugin)
+        .add_plugin(SimonClassicPlugin)
+        .add_plugin(SimonMenuPlugin)
+        .add_plugin(SimonSavePlugin)
+        .add_plugin(SimonProfilePlugin)
+        .add_event::<GameOutcome>()
         .insert_resource(KeyToArcade::default())
-        .insert_resource(UserSequence::default())
-        .insert_resource(UserProgress::default())
-        .insert_resource(None as Option<ResetState>)
-        .insert_resource(SequenceFileToLoad(Some("./current.json".into())))
-        .add_startup_system(load_sequence)
-        .add_system(arcade_event_system)
+        .init_resource::<SimonGame>()
+        .add_startup_system(spawn_cheat_sparkle)
+        .add_system(start_game)
+        .add_system_set(
+            SystemSet::on_update(SimonState::Playing)
+                .with_system(arcade_event_system)
+                .with_system(update_cheat_display_next),
+        )
+        .add_system(react_to_outcomes.after(arcade_event_system))
+        .add_system(count_save_stats.after(arcade_event_system))
+        .add_system(count_games_played)
         .add_system(fake_arcade::input_system)
-        .add_system(update_cheat_display_next)
+        .add_system(update_cheat_sparkle)
         .run();
 }
 
-fn read_sequence_from_file<P: AsRef<std::path::Path>>(
-    path: P,
-) -> Result<Vec<ArcadeInput>, Box<dyn std::error::Error>> {
-    // Open the file in read-only mode with buffer.
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(file);
-
-    // Read the JSON contents of the file as an instance of `User`.
-    let u = serde_json::from_reader(reader)?;
+fn start_game(
+    save: Res<SimonSave>,
+    mut game: ResMut<SimonGame>,
+    mut ev_start: EventReader<StartGame>,
+) {
+    for start in ev_start.iter() {
+        *game = match start.mode {
+            // Goes on with the collaborative sequence.
+            SimonMode::Extend => SimonGame::new(start.mode).with_sequence(save.sequence.clone()),
+            mode => SimonGame::new(mode),
+        };
+    }
+}
 
-    // Return the `User`.
-    Ok(u)
+/// Counts what the players did to the collaborative sequence.
+fn count_save_stats(
+    time: Res<Time>,
+    game: Res<SimonGame>,
+    mut save: ResMut<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut ev_outcomes: EventReader<GameOutcome>,
+) {
+    for GameOutcome { outcome, .. } in ev_outcomes.iter() {
+        if game.mode != SimonMode::Extend {
+            continue;
+        }
+        match outcome {
+            Outcome::Added { .. } | Outcome::Correct { .. } | Outcome::Completed { .. } => {
+                save.stats.keys_pressed += 1
+            }
+            Outcome::Mistake { .. } => save.stats.mistakes += 1,
+            Outcome::GameOver { .. } => continue,
+        }
+        autosave.mark(time.seconds_since_startup());
+    }
 }
 
-fn load_sequence(mut fileToLoad: ResMut<SequenceFileToLoad>, mut sequence: ResMut<UserSequence>) {
-    if let SequenceFileToLoad(Some(path)) = &*fileToLoad {
-        if let Ok(new_sequence) = read_sequence_from_file(path) {
-            sequence.sequence = new_sequence
-        };
+fn count_games_played(
+    time: Res<Time>,
+    mut save: ResMut<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut ev_ended: EventReader<GameEnded>,
+) {
+    for ended in ev_ended.iter() {
+        if ended.mode != SimonMode::Extend {
+            continue;
+        }
+        save.stats.games_played += 1;
+        autosave.mark(time.seconds_since_startup());
     }
-    *fileToLoad = SequenceFileToLoad(None);
 }
 
 // Read arcade input events
 fn arcade_event_system(
-    mut exit: EventWriter<AppExit>,
+    mut state: ResMut<State<SimonState>>,
     mut cheat_state: ResMut<CheatState>,
-    mut reset_state: ResMut<ResetState>,
+    mut capture_combo: ResMut<CaptureCombo>,
     time: Res<Time>,
     mut arcade_input_events: EventReader<ArcadeInputEvent>,
     mut feedback_events: EventWriter<InputReaction>,
-    mut sequence: ResMut<UserSequence>,
-    mut progress: ResMut<UserProgress>,
+    mut ev_outcomes: EventWriter<GameOutcome>,
+    mut game: ResMut<SimonGame>,
+    playback: Res<ClassicPlayback>,
 ) {
     for event in arcade_input_events.iter() {
-        const reset_button: ArcadeInput = ArcadeInput::ButtonFront2;
-        if event.value == 0f32 && event.arcade_input == reset_button {
-            reset_state.0 = false;
-            dbg!("cancel reset");
-            return;
-        }
-        if event.value == 1f32 {
+        const reset_button: ArcadeInput = simon_reset::RESET_BUTTON;
+        if capture_combo.take_press(event) {
             match &event.arcade_input {
                 ArcadeInput::ButtonFront1 => {
                     feedback_events.send(InputReaction {
@@ -123,41 +166,22 @@ fn arcade_event_system(
                     });
                     return;
                 }
-                ArcadeInput::ButtonLeftSide => {
+                &PAUSE_BUTTON => {
                     feedback_events.send(InputReaction {
                         key: event.arcade_input.clone(),
                         feedback: rust_arcade_display::FeedbackType::Menu,
                     });
-                    if let Ok(json_content) = serde_json::to_string(&sequence.sequence) {
-                        let file_path = Path::new("./current.json");
-                        match if file_path.exists() {
-                            OpenOptions::new().write(true).open(file_path)
-                        } else {
-                            fs::File::create(file_path)
-                        } {
-                            Ok(mut file) => {
-                                info!("writing to current.json");
-                                file.write_all(json_content.as_bytes()).unwrap();
-                            }
-                            Err(_) => todo!(),
-                        }
-                    }
-                    exit.send(AppExit);
+                    let _ = state.push(SimonState::Paused);
                     return;
                 }
+                // Handled by the menu.
+                ArcadeInput::ButtonLeftSide => return,
                 &reset_button => {
-                    if reset_state.0 {
-                        feedback_events.send(InputReaction {
-                            key: event.arcade_input.clone(),
-                            feedback: rust_arcade_display::FeedbackType::Cheat,
-                        });
-                        return;
-                    }
-                    reset_state.0 = true;
                     feedback_events.send(InputReaction {
                         key: event.arcade_input.clone(),
                         feedback: rust_arcade_display::FeedbackType::Menu,
                     });
+                    let _ = state.push(SimonState::Resetting);
                     return;
                 }
                 ArcadeInput::ButtonRightSide => {
@@ -182,67 +206,151 @@ fn arcade_event_system(
                     show_next_play.next_play = time.seconds_since_startup() as f32 + 0.5f32
                 }
             };
-            if sequence.sequence.len() <= progress.index {
-                // Add to the list
-                sequence.sequence.push(event.arcade_input.clone());
-                progress.index = 0;
+            if !playback.accepts_input(&game) {
                 feedback_events.send(InputReaction {
                     key: event.arcade_input.clone(),
-                    feedback: rust_arcade_display::FeedbackType::New,
-                });
-                return;
-            }
-            if event.arcade_input == sequence.sequence[progress.index] {
-                // Win!
-                progress.index += 1;
-
-                feedback_events.send(InputReaction {
-                    key: event.arcade_input.clone(),
-                    feedback: if progress.index == sequence.sequence.len() {
-                        rust_arcade_display::FeedbackType::Last
-                    } else {
-                        rust_arcade_display::FeedbackType::Good
-                    },
-                });
-            } else {
-                feedback_events.send(InputReaction {
-                    key: sequence.sequence[progress.index].clone(),
                     feedback: rust_arcade_display::FeedbackType::Cheat,
                 });
-                progress.index = 0;
-                info!(
-                    "Incorrect! progress: {}/{} ({:?})",
-                    progress.index,
-                    sequence.sequence.len(),
-                    event.arcade_input
-                );
-                feedback_events.send(InputReaction {
-                    key: event.arcade_input.clone(),
-                    feedback: rust_arcade_display::FeedbackType::Bad,
+                continue;
+            }
+            for outcome in game.handle_input(event.arcade_input.clone()) {
+                ev_outcomes.send(GameOutcome {
+                    input: event.arcade_input.clone(),
+                    outcome,
                 });
             }
         }
     }
 }
 
+fn react_to_outcomes(
+    game: Res<SimonGame>,
+    mut ev_outcomes: EventReader<GameOutcome>,
+    mut ev_ended: EventWriter<GameEnded>,
+    mut feedback_events: EventWriter<InputReaction>,
+    mut popups: EventWriter<PopupText>,
+) {
+    for GameOutcome { input, outcome } in ev_outcomes.iter() {
+        if let Outcome::GameOver { score } = outcome {
+            ev_ended.send(GameEnded {
+                mode: game.mode,
+                score: *score,
+                game_over: true,
+            });
+        }
+        send_outcome(input, outcome.clone(), &mut feedback_events, &mut popups);
+    }
+}
+
+/// Gives feedback for what happened in the game after `input` was pressed.
+fn send_outcome(
+    input: &ArcadeInput,
+    outcome: Outcome,
+    feedback_events: &mut EventWriter<InputReaction>,
+    popups: &mut EventWriter<PopupText>,
+) {
+    match outcome {
+        Outcome::Added { key, length } => {
+            feedback_events.send(InputReaction {
+                key: key.clone(),
+                feedback: rust_arcade_display::FeedbackType::New,
+            });
+            popups.send(if length % 5 == 0 {
+                PopupText::new(key, format!("{}!", length)).with_color(Color::GOLD)
+            } else {
+                PopupText::new(key, "+1")
+            });
+        }
+        Outcome::Correct { key } => feedback_events.send(InputReaction {
+            key,
+            feedback: rust_arcade_display::FeedbackType::Good,
+        }),
+        Outcome::Completed { key, .. } => feedback_events.send(InputReaction {
+            key,
+            feedback: rust_arcade_display::FeedbackType::Last,
+        }),
+        Outcome::Mistake { expected, pressed } => {
+            info!("Incorrect! expected {:?}, got {:?}", expected, pressed);
+            feedback_events.send(InputReaction {
+                key: expected,
+                feedback: rust_arcade_display::FeedbackType::Cheat,
+            });
+            feedback_events.send(InputReaction {
+                key: pressed,
+                feedback: rust_arcade_display::FeedbackType::Bad,
+            });
+        }
+        Outcome::GameOver { score } => {
+            popups.send(
+                PopupText::new(input.clone(), format!("Game over: {}", score))
+                    .with_color(Color::ORANGE_RED),
+            );
+        }
+    }
+}
+
 fn update_cheat_display_next(
     mut cheat_state: ResMut<CheatState>,
     time: Res<Time>,
     mut feedback_events: EventWriter<InputReaction>,
-    sequence: Res<UserSequence>,
-    mut progress: Res<UserProgress>,
+    game: Res<SimonGame>,
 ) {
     if let CheatState::ShowNextPlay(ref mut show_next_play) = *cheat_state {
-        if sequence.sequence.len() <= progress.index {
-            return;
-        }
+        let next = match game.next_expected() {
+            Some(next) => next,
+            None => return,
+        };
         let current_time = time.seconds_since_startup() as f32;
         if show_next_play.next_play <= current_time {
             feedback_events.send(InputReaction {
-                key: sequence.sequence[progress.index].clone(),
+                key: next.clone(),
                 feedback: rust_arcade_display::FeedbackType::Cheat,
             });
             show_next_play.next_play = current_time + show_next_play.delta_between_displays;
         }
     }
 }
+
+fn spawn_cheat_sparkle(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut emitter = ParticleEmitter::new(
+        asset_server.load("particles/sparkle.particles.json"),
+        Color::WHITE,
+        30f32,
+    );
+    emitter.stop();
+    commands
+        .spawn_bundle(TransformBundle::default())
+        .insert(emitter)
+        .insert(CheatSparkle);
+}
+
+fn update_cheat_sparkle(
+    state: Res<State<SimonState>>,
+    cheat_state: Res<CheatState>,
+    game: Res<SimonGame>,
+    q_reactables: Query<(&Transform, &Reactable), Without<CheatSparkle>>,
+    mut q_sparkle: Query<(&mut Transform, &mut ParticleEmitter), With<CheatSparkle>>,
+) {
+    let next_key = match *cheat_state {
+        CheatState::ShowNextPlay(_) if *state.current() == SimonState::Playing => {
+            game.next_expected()
+        }
+        _ => None,
+    };
+    let target = next_key.and_then(|key| {
+        q_reactables
+            .iter()
+            .find(|(_, reactable)| &reactable.key == key)
+            .map(|(transform, _)| transform.translation)
+    });
+    for (mut transform, mut emitter) in q_sparkle.iter_mut() {
+        match target {
+            Some(translation) => {
+                transform.translation = translation;
+                emitter.start();
+            }
+            None if emitter.is_active() => emitter.stop(),
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use rust_arcade::bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent};
+use rust_arcade_display::{
+    camera_effects::CameraEffect,
+    capture::{CaptureFrame, FrameCaptured},
+    popup_text::PopupText,
+};
+use serde::Serialize;
+
+use crate::{simon_game::SimonGame, simon_save::SavePath};
+
+pub struct SimonCapturePlugin;
+
+impl Plugin for SimonCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CaptureCombo {
+            keys: vec![ArcadeInput::ButtonFront1, ArcadeInput::ButtonRightSide],
+            held: HashSet::new(),
+            pending: HashSet::new(),
+        })
+        .init_resource::<CaptureFolder>()
+        .add_system(capture_on_combo.before(crate::arcade_event_system))
+        .add_system(show_captured);
+    }
+}
+
+/// Holding all `keys` together captures the screen and a summary of the progress.
+pub struct CaptureCombo {
+    pub keys: Vec<ArcadeInput>,
+    held: HashSet<ArcadeInput>,
+    /// Keys of the combo pressed since their last release, which didn't take a capture.
+    pending: HashSet<ArcadeInput>,
+}
+
+impl CaptureCombo {
+    /// Follows the keys of the combo, returns whether `event` completes it.
+    fn track(&mut self, event: &ArcadeInputEvent) -> bool {
+        if !self.keys.contains(&event.arcade_input) {
+            return false;
+        }
+        if event.value == 0f32 {
+            self.held.remove(&event.arcade_input);
+            return false;
+        }
+        self.held.insert(event.arcade_input.clone());
+        self.pending.insert(event.arcade_input.clone());
+        if !self.keys.iter().all(|k| self.held.contains(k)) {
+            return false;
+        }
+        self.pending.clear();
+        true
+    }
+
+    /// Whether `event` is a press to handle. Keys of the combo are handled once released, and
+    /// only if they weren't part of a capture, so taking one doesn't press them too.
+    pub fn take_press(&mut self, event: &ArcadeInputEvent) -> bool {
+        if !self.keys.contains(&event.arcade_input) {
+            return event.value == 1f32;
+        }
+        event.value == 0f32 && self.pending.remove(&event.arcade_input)
+    }
+}
+
+/// Where captures are written, a `captures` directory next to the save by default.
+pub struct CaptureFolder(pub PathBuf);
+
+impl FromWorld for CaptureFolder {
+    fn from_world(world: &mut World) -> Self {
+        let save_path = &world.get_resource_or_insert_with(SavePath::default).0;
+        Self(
+            save_path
+                .parent()
+                .map(|dir| dir.join("captures"))
+                .unwrap_or_else(|| PathBuf::from("captures")),
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct CaptureSummary {
+    sequence_length: usize,
+    /// Seconds since unix epoch.
+    date: u64,
+    image: String,
+}
+
+fn capture_on_combo(
+    mut combo: ResMut<CaptureCombo>,
+    folder: Res<CaptureFolder>,
+    game: Res<SimonGame>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
+    mut captures: EventWriter<CaptureFrame>,
+) {
+    for event in arcade_input_events.iter() {
+        if !combo.track(event) {
+            continue;
+        }
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let date = since_epoch.as_secs();
+        // Milliseconds too, so captures taken within a second don't overwrite each other.
+        let name = format!("simon-{}-{:03}", date, since_epoch.subsec_millis());
+        let image = format!("{}.png", name);
+        let summary = CaptureSummary {
+            sequence_length: game.sequence().len(),
+            date,
+            image: image.clone(),
+        };
+        match write_summary(&folder.0.join(format!("{}.json", name)), &summary) {
+            Ok(()) => captures.send(CaptureFrame {
+                path: folder.0.join(image),
+            }),
+            Err(e) => error!("Could not write capture summary: {}", e),
+        }
+    }
+}
+
+fn show_captured(
+    combo: Res<CaptureCombo>,
+    mut captured: EventReader<FrameCaptured>,
+    mut camera_effects: EventWriter<CameraEffect>,
+    mut popups: EventWriter<PopupText>,
+) {
+    for _ in captured.iter() {
+        camera_effects.send(CameraEffect {
+            flash: Some(Color::rgba(1f32, 1f32, 1f32, 0.6f32)),
+            ..default()
+        });
+        if let Some(key) = combo.keys.last() {
+            popups.send(PopupText::new(key.clone(), "Saved!"));
+        }
+    }
+}
+
+fn write_summary(path: &Path, summary: &CaptureSummary) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(summary)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(arcade_input: ArcadeInput, value: f32) -> ArcadeInputEvent {
+        ArcadeInputEvent {
+            gamepad: Gamepad(0),
+            arcade_input,
+            value,
+        }
+    }
+
+    fn combo() -> CaptureCombo {
+        CaptureCombo {
+            keys: vec![ArcadeInput::ButtonFront1, ArcadeInput::ButtonRightSide],
+            held: HashSet::new(),
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Tracks `events` then returns those handled as presses, like `capture_on_combo` runs
+    /// before `arcade_event_system`.
+    fn presses(combo: &mut CaptureCombo, events: &[ArcadeInputEvent]) -> (bool, Vec<ArcadeInput>) {
+        let captured = events.iter().filter(|e| combo.track(e)).count() > 0;
+        let pressed = events
+            .iter()
+            .filter(|e| combo.take_press(e))
+            .map(|e| e.arcade_input.clone())
+            .collect();
+        (captured, pressed)
+    }
+
+    #[test]
+    fn combo_keys_are_pressed_on_release() {
+        let mut combo = combo();
+        let (_, pressed) = presses(&mut combo, &[event(ArcadeInput::ButtonFront1, 1f32)]);
+        assert!(pressed.is_empty());
+        let (_, pressed) = presses(&mut combo, &[event(ArcadeInput::ButtonFront1, 0f32)]);
+        assert_eq!(pressed, vec![ArcadeInput::ButtonFront1]);
+        let (_, pressed) = presses(&mut combo, &[event(ArcadeInput::JoyUp, 1f32)]);
+        assert_eq!(pressed, vec![ArcadeInput::JoyUp]);
+    }
+
+    #[test]
+    fn capture_swallows_both_keys() {
+        let mut combo = combo();
+        let steps = [
+            (ArcadeInput::ButtonRightSide, 1f32, false),
+            (ArcadeInput::ButtonFront1, 1f32, true),
+            (ArcadeInput::ButtonRightSide, 0f32, false),
+            (ArcadeInput::ButtonFront1, 0f32, false),
+        ];
+        for (key, value, captures) in steps {
+            let (captured, pressed) = presses(&mut combo, &[event(key, value)]);
+            assert_eq!(captured, captures);
+            assert!(pressed.is_empty());
+        }
+        let (_, pressed) = presses(
+            &mut combo,
+            &[
+                event(ArcadeInput::ButtonRightSide, 1f32),
+                event(ArcadeInput::ButtonRightSide, 0f32),
+            ],
+        );
+        assert_eq!(pressed, vec![ArcadeInput::ButtonRightSide]);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+use game_rng::GameRng;
+use rand::seq::SliceRandom;
+use rust_arcade::bevy_rust_arcade::ArcadeInput;
+use rust_arcade_display::{
+    FeedbackSound, FeedbackStyle, FeedbackStyles, FeedbackType, InputReaction,
+};
+
+use crate::{
+    simon_game::{Outcome, SimonGame, SimonMode},
+    simon_menu::StartGame,
+    simon_state::SimonState,
+    GameOutcome,
+};
+
+/// Flash of a key played back by the game.
+pub const FEEDBACK_PLAYBACK: FeedbackType = FeedbackType::Custom("playback");
+
+/// Computer-led simon: the game adds a random key every round and plays the sequence back.
+pub struct SimonClassicPlugin;
+
+impl Plugin for SimonClassicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClassicSettings>()
+            .init_resource::<ClassicPlayback>()
+            .init_resource::<GameRng>()
+            .add_startup_system(register_feedback_style)
+            .add_system(reset_playback)
+            .add_system(follow_outcomes)
+            .add_system_set(
+                SystemSet::on_update(SimonState::Playing)
+                    .with_system(
+                        start_round
+                            .after(reset_playback)
+                            .after(crate::arcade_event_system)
+                            .after(check_answer_time),
+                    )
+                    .with_system(play_sequence.after(start_round))
+                    .with_system(check_answer_time.after(follow_outcomes)),
+            )
+            .add_system_set(SystemSet::on_enter(SimonState::Playing).with_system(resume_playback))
+            .add_system_set(SystemSet::on_resume(SimonState::Playing).with_system(resume_playback));
+    }
+}
+
+pub struct ClassicSettings {
+    /// Keys the game picks from.
+    pub keys: Vec<ArcadeInput>,
+    /// Seconds before a new round is played back.
+    pub delay_before_round: f32,
+    /// Seconds between two keys of the first rounds.
+    pub interval: f32,
+    /// Seconds removed from `interval` for every key in the sequence.
+    pub speedup: f32,
+    pub min_interval: f32,
+    /// Seconds to press each key in timed mode.
+    pub answer_time: f32,
+}
+
+impl Default for ClassicSettings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                ArcadeInput::JoyUp,
+                ArcadeInput::JoyDown,
+                ArcadeInput::JoyLeft,
+                ArcadeInput::JoyRight,
+                ArcadeInput::ButtonTop1,
+                ArcadeInput::ButtonTop2,
+                ArcadeInput::ButtonTop3,
+                ArcadeInput::ButtonTop4,
+            ],
+            delay_before_round: 1f32,
+            interval: 0.8f32,
+            speedup: 0.04f32,
+            min_interval: 0.25f32,
+            answer_time: 3f32,
+        }
+    }
+}
+
+impl ClassicSettings {
+    pub fn interval_for(&self, length: usize) -> f32 {
+        (self.interval - self.speedup * length as f32).max(self.min_interval)
+    }
+}
+
+/// Progress of the sequence being played back.
+#[derive(Default)]
+pub struct ClassicPlayback {
+    /// Index of the next key to play, `None` when not playing.
+    next_key: Option<usize>,
+    next_time: f64,
+    /// When the players last pressed a key or the playback ended.
+    last_action: f64,
+}
+
+impl ClassicPlayback {
+    pub fn is_playing(&self) -> bool {
+        self.next_key.is_some()
+    }
+
+    /// Whether the player can press keys, they can't while the game is playing.
+    pub fn accepts_input(&self, game: &SimonGame) -> bool {
+        !game.mode.is_computer_led() || !(self.is_playing() || game.is_waiting_for_new_key())
+    }
+
+    /// Plays the sequence again from the beginning, after `delay` seconds.
+    pub fn replay(&mut self, now: f64, delay: f32) {
+        self.next_key = Some(0);
+        self.next_time = now + delay as f64;
+    }
+
+    /// Restarts the clocks when coming back to the game, so time spent away doesn't count.
+    pub fn resume(&mut self, now: f64) {
+        self.next_time = self.next_time.max(now);
+        self.last_action = now;
+    }
+}
+
+fn register_feedback_style(mut styles: ResMut<FeedbackStyles>) {
+    styles.register(
+        FEEDBACK_PLAYBACK,
+        FeedbackStyle::new(Color::WHITE)
+            .with_sound(FeedbackSound::Key)
+            .with_particles(None)
+            .with_priority(0),
+    );
+}
+
+fn reset_playback(mut playback: ResMut<ClassicPlayback>, mut ev_start: EventReader<StartGame>) {
+    if ev_start.iter().count() > 0 {
+        *playback = ClassicPlayback::default();
+    }
+}
+
+fn resume_playback(time: Res<Time>, mut playback: ResMut<ClassicPlayback>) {
+    playback.resume(time.seconds_since_startup());
+}
+
+/// Adds a key once the sequence is replayed. Runs after the inputs and timeouts of the frame,
+/// so a game over, which clears the game before leaving [`SimonState::Playing`], doesn't start
+/// a new round.
+fn start_round(
+    time: Res<Time>,
+    settings: Res<ClassicSettings>,
+    mut game_rng: ResMut<GameRng>,
+    mut game: ResMut<SimonGame>,
+    mut playback: ResMut<ClassicPlayback>,
+    mut ev_outcomes: EventReader<GameOutcome>,
+) {
+    let game_over = ev_outcomes
+        .iter()
+        .filter(|GameOutcome { outcome, .. }| matches!(outcome, Outcome::GameOver { .. }))
+        .count()
+        > 0;
+    if game_over
+        || !game.mode.is_computer_led()
+        || playback.is_playing()
+        || !game.is_waiting_for_new_key()
+    {
+        return;
+    }
+    if let Some(key) = settings.keys.choose(game_rng.stream("simon_classic")) {
+        game.add_key(key.clone());
+        playback.replay(time.seconds_since_startup(), settings.delay_before_round);
+    }
+}
+
+fn play_sequence(
+    time: Res<Time>,
+    settings: Res<ClassicSettings>,
+    game: Res<SimonGame>,
+    mut playback: ResMut<ClassicPlayback>,
+    mut feedback_events: EventWriter<InputReaction>,
+) {
+    let index = match playback.next_key {
+        Some(index) => index,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+    if now < playback.next_time {
+        return;
+    }
+    let sequence = game.sequence();
+    if let Some(key) = sequence.get(index) {
+        feedback_events.send(InputReaction {
+            key: key.clone(),
+            feedback: FEEDBACK_PLAYBACK,
+        });
+    }
+    playback.next_key = Some(index + 1).filter(|next| *next < sequence.len());
+    playback.next_time = now + settings.interval_for(sequence.len()) as f64;
+    playback.last_action = playback.next_time;
+}
+
+fn follow_outcomes(
+    time: Res<Time>,
+    settings: Res<ClassicSettings>,
+    game: Res<SimonGame>,
+    mut playback: ResMut<ClassicPlayback>,
+    mut ev_outcomes: EventReader<GameOutcome>,
+) {
+    let now = time.seconds_since_startup();
+    for GameOutcome { outcome, .. } in ev_outcomes.iter() {
+        playback.last_action = now;
+        if let Outcome::Mistake { .. } = outcome {
+            if game.mode == SimonMode::Practice {
+                playback.replay(now, settings.delay_before_round);
+            }
+        }
+    }
+}
+
+/// Ends timed games when the players take too long to press the next key.
+fn check_answer_time(
+    time: Res<Time>,
+    settings: Res<ClassicSettings>,
+    mut game: ResMut<SimonGame>,
+    playback: Res<ClassicPlayback>,
+    mut ev_outcomes: EventWriter<GameOutcome>,
+) {
+    if game.mode != SimonMode::Timed || playback.is_playing() {
+        return;
+    }
+    let expected = match game.next_expected() {
+        Some(expected) => expected.clone(),
+        None => return,
+    };
+    if time.seconds_since_startup() - playback.last_action < settings.answer_time as f64 {
+        return;
+    }
+    for outcome in game.timeout() {
+        ev_outcomes.send(GameOutcome {
+            input: expected.clone(),
+            outcome,
+        });
+    }
+}
@@ -0,0 +1,327 @@
+use rust_arcade::bevy_rust_arcade::ArcadeInput;
+use serde::{Deserialize, Serialize};
+
+/// How the sequence grows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SimonMode {
+    /// Players take turns: once the sequence is replayed, the next key pressed is added to it.
+    #[default]
+    Extend,
+    /// The game picks the next key with [`SimonGame::add_key`] and plays the sequence back,
+    /// a mistake ends the game.
+    Classic,
+    /// Classic, with a limited time to press each key, see [`SimonGame::timeout`].
+    Timed,
+    /// Classic, where a mistake only replays the sequence.
+    Practice,
+}
+
+impl SimonMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimonMode::Extend => "Collaborative",
+            SimonMode::Classic => "Classic",
+            SimonMode::Timed => "Timed",
+            SimonMode::Practice => "Practice",
+        }
+    }
+
+    /// Whether the game picks the keys, instead of the players.
+    pub fn is_computer_led(&self) -> bool {
+        *self != SimonMode::Extend
+    }
+
+    pub fn ends_on_mistake(&self) -> bool {
+        matches!(self, SimonMode::Classic | SimonMode::Timed)
+    }
+}
+
+/// What happened after an input, for the Bevy layer to give feedback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// `key` was added at the end of the sequence, which is now `length` long.
+    Added { key: ArcadeInput, length: usize },
+    /// `key` was the expected one, more keys are left to replay.
+    Correct { key: ArcadeInput },
+    /// `key` was the last one of the sequence.
+    Completed { key: ArcadeInput, length: usize },
+    /// `pressed` was not `expected`, replay starts over.
+    Mistake {
+        expected: ArcadeInput,
+        pressed: ArcadeInput,
+    },
+    /// After a mistake in modes which end on one: the sequence is cleared, `score` keys were
+    /// remembered.
+    GameOver { score: usize },
+}
+
+/// Rules of simon, independent of Bevy.
+#[derive(Debug, Clone, Default)]
+pub struct SimonGame {
+    pub mode: SimonMode,
+    sequence: Vec<ArcadeInput>,
+    index: usize,
+}
+
+impl SimonGame {
+    pub fn new(mode: SimonMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_sequence(mut self, sequence: Vec<ArcadeInput>) -> Self {
+        self.sequence = sequence;
+        self.index = 0;
+        self
+    }
+
+    pub fn sequence(&self) -> &[ArcadeInput] {
+        &self.sequence
+    }
+
+    /// Number of keys replayed correctly so far.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Key to press next to replay the sequence, `None` when a new key is expected.
+    pub fn next_expected(&self) -> Option<&ArcadeInput> {
+        self.sequence.get(self.index)
+    }
+
+    pub fn is_waiting_for_new_key(&self) -> bool {
+        self.next_expected().is_none()
+    }
+
+    /// Ratio of the sequence replayed, 0 for an empty sequence.
+    pub fn progress_ratio(&self) -> f32 {
+        if self.sequence.is_empty() {
+            return 0f32;
+        }
+        self.index as f32 / self.sequence.len() as f32
+    }
+
+    /// Length of the longest sequence replayed so far.
+    pub fn score(&self) -> usize {
+        if self.is_waiting_for_new_key() {
+            self.sequence.len()
+        } else {
+            self.sequence.len().saturating_sub(1)
+        }
+    }
+
+    /// Forgets the sequence.
+    pub fn clear(&mut self) {
+        self.sequence.clear();
+        self.index = 0;
+    }
+
+    /// Adds `key` at the end of the sequence, and starts replaying it from the beginning.
+    pub fn add_key(&mut self, key: ArcadeInput) -> Outcome {
+        self.sequence.push(key.clone());
+        self.index = 0;
+        Outcome::Added {
+            key,
+            length: self.sequence.len(),
+        }
+    }
+
+    pub fn handle_input(&mut self, key: ArcadeInput) -> Vec<Outcome> {
+        let expected = match self.next_expected() {
+            Some(expected) => expected.clone(),
+            None if self.mode.is_computer_led() => return vec![],
+            None => return vec![self.add_key(key)],
+        };
+        if key != expected {
+            self.index = 0;
+            let mut outcomes = vec![Outcome::Mistake {
+                expected,
+                pressed: key,
+            }];
+            outcomes.extend(self.game_over());
+            return outcomes;
+        }
+        self.index += 1;
+        if self.index == self.sequence.len() {
+            vec![Outcome::Completed {
+                key,
+                length: self.sequence.len(),
+            }]
+        } else {
+            vec![Outcome::Correct { key }]
+        }
+    }
+
+    /// The players took too long to press the next key.
+    pub fn timeout(&mut self) -> Vec<Outcome> {
+        self.index = 0;
+        self.game_over().into_iter().collect()
+    }
+
+    fn game_over(&mut self) -> Option<Outcome> {
+        if !self.mode.ends_on_mistake() {
+            return None;
+        }
+        let score = self.score();
+        self.clear();
+        Some(Outcome::GameOver { score })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ArcadeInput::*;
+
+    fn game(sequence: &[ArcadeInput]) -> SimonGame {
+        SimonGame::new(SimonMode::Extend).with_sequence(sequence.to_vec())
+    }
+
+    #[test]
+    fn empty_game_adds_first_key() {
+        let mut simon = SimonGame::default();
+        assert!(simon.is_waiting_for_new_key());
+        assert_eq!(
+            simon.handle_input(JoyUp),
+            vec![Outcome::Added {
+                key: JoyUp,
+                length: 1
+            }]
+        );
+        assert_eq!(simon.sequence(), &[JoyUp]);
+        assert_eq!(simon.next_expected(), Some(&JoyUp));
+    }
+
+    #[test]
+    fn replaying_advances_then_completes() {
+        let mut simon = game(&[JoyUp, JoyDown]);
+        assert_eq!(
+            simon.handle_input(JoyUp),
+            vec![Outcome::Correct { key: JoyUp }]
+        );
+        assert_eq!(simon.index(), 1);
+        assert_eq!(
+            simon.handle_input(JoyDown),
+            vec![Outcome::Completed {
+                key: JoyDown,
+                length: 2
+            }]
+        );
+        assert!(simon.is_waiting_for_new_key());
+    }
+
+    #[test]
+    fn key_after_completion_extends_and_restarts() {
+        let mut simon = game(&[JoyUp]);
+        simon.handle_input(JoyUp);
+        assert_eq!(
+            simon.handle_input(JoyLeft),
+            vec![Outcome::Added {
+                key: JoyLeft,
+                length: 2
+            }]
+        );
+        assert_eq!(simon.index(), 0);
+        assert_eq!(simon.next_expected(), Some(&JoyUp));
+    }
+
+    #[test]
+    fn mistake_restarts_without_changing_sequence() {
+        let mut simon = game(&[JoyUp, JoyDown, JoyLeft]);
+        simon.handle_input(JoyUp);
+        assert_eq!(
+            simon.handle_input(JoyRight),
+            vec![Outcome::Mistake {
+                expected: JoyDown,
+                pressed: JoyRight
+            }]
+        );
+        assert_eq!(simon.index(), 0);
+        assert_eq!(simon.sequence(), &[JoyUp, JoyDown, JoyLeft]);
+    }
+
+    #[test]
+    fn progress_ratio_follows_index() {
+        let mut simon = game(&[JoyUp, JoyDown, JoyUp, JoyDown]);
+        assert_eq!(simon.progress_ratio(), 0f32);
+        simon.handle_input(JoyUp);
+        assert_eq!(simon.progress_ratio(), 0.25f32);
+        assert_eq!(SimonGame::default().progress_ratio(), 0f32);
+    }
+
+    #[test]
+    fn classic_ignores_input_until_a_key_is_added() {
+        let mut simon = SimonGame::new(SimonMode::Classic);
+        assert!(simon.handle_input(JoyUp).is_empty());
+        assert!(simon.sequence().is_empty());
+        simon.add_key(JoyDown);
+        assert_eq!(
+            simon.handle_input(JoyDown),
+            vec![Outcome::Completed {
+                key: JoyDown,
+                length: 1
+            }]
+        );
+        assert!(simon.handle_input(JoyDown).is_empty());
+    }
+
+    #[test]
+    fn classic_mistake_is_game_over() {
+        let mut simon = SimonGame::new(SimonMode::Classic).with_sequence(vec![JoyUp, JoyDown]);
+        assert_eq!(
+            simon.handle_input(JoyLeft),
+            vec![
+                Outcome::Mistake {
+                    expected: JoyUp,
+                    pressed: JoyLeft
+                },
+                Outcome::GameOver { score: 1 },
+            ]
+        );
+        assert!(simon.sequence().is_empty());
+    }
+
+    #[test]
+    fn practice_mistake_keeps_sequence() {
+        let mut simon = SimonGame::new(SimonMode::Practice).with_sequence(vec![JoyUp, JoyDown]);
+        simon.handle_input(JoyUp);
+        assert_eq!(
+            simon.handle_input(JoyUp),
+            vec![Outcome::Mistake {
+                expected: JoyDown,
+                pressed: JoyUp
+            }]
+        );
+        assert_eq!(simon.sequence(), &[JoyUp, JoyDown]);
+        assert_eq!(simon.next_expected(), Some(&JoyUp));
+    }
+
+    #[test]
+    fn timed_timeout_is_game_over() {
+        let mut simon = SimonGame::new(SimonMode::Timed).with_sequence(vec![JoyUp, JoyDown]);
+        simon.handle_input(JoyUp);
+        assert_eq!(simon.timeout(), vec![Outcome::GameOver { score: 1 }]);
+        assert!(simon.sequence().is_empty());
+        assert!(game(&[JoyUp]).timeout().is_empty());
+    }
+
+    #[test]
+    fn score_counts_replayed_sequence() {
+        let mut simon = game(&[JoyUp, JoyDown]);
+        assert_eq!(simon.score(), 1);
+        simon.handle_input(JoyUp);
+        simon.handle_input(JoyDown);
+        assert_eq!(simon.score(), 2);
+    }
+
+    #[test]
+    fn clear_forgets_sequence() {
+        let mut simon = game(&[JoyUp, JoyDown]);
+        simon.handle_input(JoyUp);
+        simon.clear();
+        assert!(simon.sequence().is_empty());
+        assert_eq!(simon.index(), 0);
+    }
+}
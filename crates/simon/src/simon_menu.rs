@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use bevy::{app::AppExit, prelude::*};
+use rust_arcade::bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent};
+use rust_arcade_display::{DisplayTheme, FeedbackType, InputReaction};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    simon_game::{SimonGame, SimonMode},
+    simon_save::{Autosave, SimonSave},
+    simon_state::SimonState,
+};
+
+pub struct SimonMenuPlugin;
+
+impl Plugin for SimonMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimonMenu>()
+            .add_event::<StartGame>()
+            .add_event::<GameEnded>()
+            .add_startup_system(spawn_menu)
+            .add_system(record_game_ended)
+            .add_system(update_menu_texts)
+            .add_system_set(SystemSet::on_enter(SimonState::Menu).with_system(show_menu))
+            .add_system_set(SystemSet::on_update(SimonState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_exit(SimonState::Menu).with_system(hide_menu))
+            .add_system_set(SystemSet::on_enter(SimonState::Profile).with_system(hide_menu))
+            .add_system_set(SystemSet::on_update(SimonState::Playing).with_system(leave_game));
+    }
+}
+
+/// Modes listed in the menu, in order.
+pub const MENU_MODES: [SimonMode; 4] = [
+    SimonMode::Extend,
+    SimonMode::Classic,
+    SimonMode::Timed,
+    SimonMode::Practice,
+];
+
+/// Mode selection, shown in [`SimonState::Menu`].
+#[derive(Default)]
+pub struct SimonMenu {
+    pub selected: usize,
+}
+
+/// Best score of each mode, see [`crate::simon_game::SimonGame::score`]. Kept in the
+/// [`SimonSave`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BestScores(pub HashMap<SimonMode, usize>);
+
+impl BestScores {
+    pub fn get(&self, mode: SimonMode) -> usize {
+        self.0.get(&mode).copied().unwrap_or(0)
+    }
+
+    /// Returns whether `score` is a new best.
+    pub fn record(&mut self, mode: SimonMode, score: usize) -> bool {
+        let best = self.0.entry(mode).or_insert(0);
+        if score <= *best {
+            return false;
+        }
+        *best = score;
+        true
+    }
+}
+
+/// Sent when a mode is picked in the menu.
+pub struct StartGame {
+    pub mode: SimonMode,
+}
+
+/// Sent when a game is lost, or left to the menu.
+pub struct GameEnded {
+    pub mode: SimonMode,
+    pub score: usize,
+    /// Lost rather than left.
+    pub game_over: bool,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuEntry(usize);
+
+fn spawn_menu(mut commands: Commands, theme: Res<DisplayTheme>) {
+    let font = theme.font.clone();
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0f32, 0f32, 0f32, 0.85f32),
+                custom_size: Some(Vec2::splat(10000f32)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0f32, 0f32, 800f32)),
+            ..default()
+        })
+        .insert(MenuRoot);
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "Choose a mode",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 80.0,
+                    color: Color::rgb(0.7, 0.7, 1.0),
+                },
+                text_alignment,
+            ),
+            transform: Transform::from_translation(Vec3::new(0f32, 300f32, 810f32)),
+            ..default()
+        })
+        .insert(MenuRoot);
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "< change profile",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    color: Color::GRAY,
+                },
+                text_alignment,
+            ),
+            transform: Transform::from_translation(Vec3::new(0f32, -350f32, 810f32)),
+            ..default()
+        })
+        .insert(MenuRoot);
+    for (i, _) in MENU_MODES.iter().enumerate() {
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 60.0,
+                        color: Color::WHITE,
+                    },
+                    text_alignment,
+                ),
+                transform: Transform::from_translation(Vec3::new(
+                    0f32,
+                    150f32 - i as f32 * 100f32,
+                    810f32,
+                )),
+                ..default()
+            })
+            .insert(MenuEntry(i))
+            .insert(MenuRoot);
+    }
+}
+
+fn show_menu(mut q_visibility: Query<&mut Visibility, With<MenuRoot>>) {
+    for mut visibility in q_visibility.iter_mut() {
+        visibility.is_visible = true;
+    }
+}
+
+fn hide_menu(mut q_visibility: Query<&mut Visibility, With<MenuRoot>>) {
+    for mut visibility in q_visibility.iter_mut() {
+        visibility.is_visible = false;
+    }
+}
+
+fn menu_input(
+    mut state: ResMut<State<SimonState>>,
+    mut menu: ResMut<SimonMenu>,
+    mut exit: EventWriter<AppExit>,
+    mut ev_start: EventWriter<StartGame>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
+    mut feedback_events: EventWriter<InputReaction>,
+) {
+    for event in arcade_input_events.iter() {
+        if event.value != 1f32 {
+            continue;
+        }
+        match event.arcade_input {
+            ArcadeInput::JoyUp => {
+                menu.selected = (menu.selected + MENU_MODES.len() - 1) % MENU_MODES.len();
+            }
+            ArcadeInput::JoyDown => {
+                menu.selected = (menu.selected + 1) % MENU_MODES.len();
+            }
+            ArcadeInput::JoyLeft => {
+                let _ = state.set(SimonState::Profile);
+            }
+            ArcadeInput::JoyButton => {
+                ev_start.send(StartGame {
+                    mode: MENU_MODES[menu.selected],
+                });
+                let _ = state.set(SimonState::Playing);
+            }
+            ArcadeInput::ButtonLeftSide => {
+                exit.send(AppExit);
+            }
+            _ => continue,
+        }
+        feedback_events.send(InputReaction {
+            key: event.arcade_input.clone(),
+            feedback: FeedbackType::Menu,
+        });
+    }
+}
+
+/// Goes back to the menu from a game.
+fn leave_game(
+    mut state: ResMut<State<SimonState>>,
+    game: Res<SimonGame>,
+    mut ev_ended: EventWriter<GameEnded>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
+    mut feedback_events: EventWriter<InputReaction>,
+) {
+    for event in arcade_input_events.iter() {
+        if event.value == 1f32 && event.arcade_input == ArcadeInput::ButtonLeftSide {
+            feedback_events.send(InputReaction {
+                key: event.arcade_input.clone(),
+                feedback: FeedbackType::Menu,
+            });
+            ev_ended.send(GameEnded {
+                mode: game.mode,
+                score: game.score(),
+                game_over: false,
+            });
+            let _ = state.set(SimonState::Menu);
+            return;
+        }
+    }
+}
+
+fn record_game_ended(
+    time: Res<Time>,
+    mut save: ResMut<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut ev_ended: EventReader<GameEnded>,
+) {
+    for ended in ev_ended.iter() {
+        if save.best_scores.record(ended.mode, ended.score) {
+            autosave.mark(time.seconds_since_startup());
+        }
+    }
+}
+
+fn update_menu_texts(
+    menu: Res<SimonMenu>,
+    save: Res<SimonSave>,
+    mut q_entries: Query<(&mut Text, &MenuEntry)>,
+) {
+    if !menu.is_changed() && !save.is_changed() {
+        return;
+    }
+    for (mut text, entry) in q_entries.iter_mut() {
+        let mode = MENU_MODES[entry.0];
+        let selected = entry.0 == menu.selected;
+        text.sections[0].value = format!(
+            "{}{}   best: {}",
+            if selected { "> " } else { "" },
+            mode.name(),
+            save.best_scores.get(mode)
+        );
+        text.sections[0].style.color = if selected { Color::GOLD } else { Color::WHITE };
+    }
+}
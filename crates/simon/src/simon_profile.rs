@@ -0,0 +1,382 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use rust_arcade::bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent};
+use rust_arcade_display::{DisplayTheme, FeedbackType, InputReaction};
+
+use crate::{
+    simon_game::SimonGame,
+    simon_save::{Autosave, SavePath, SimonSave},
+    simon_state::SimonState,
+};
+
+/// Profile using the save path given on the command line, shared by everyone who doesn't
+/// pick a name.
+pub const SHARED_PROFILE: &str = "Shared";
+
+/// Characters of the on-screen keyboard, followed by [`DELETE_KEY`] and [`OK_KEY`].
+const KEYBOARD: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const KEYBOARD_COLUMNS: usize = 10;
+const DELETE_KEY: usize = KEYBOARD.len();
+const OK_KEY: usize = KEYBOARD.len() + 1;
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Named profiles, each with its own save. Picked before the mode menu.
+pub struct SimonProfilePlugin;
+
+impl Plugin for SimonProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Profiles>()
+            .init_resource::<ProfileScreen>()
+            .add_system_set(
+                SystemSet::on_enter(SimonState::Profile).with_system(open_profile_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(SimonState::Profile)
+                    .with_system(profile_input)
+                    .with_system(draw_profile_screen.after(profile_input)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(SimonState::Profile).with_system(close_profile_screen),
+            );
+    }
+}
+
+/// Known profiles and the active one.
+pub struct Profiles {
+    /// Save of [`SHARED_PROFILE`], other profiles are saved in a `profiles` directory next to it.
+    shared_path: PathBuf,
+    active: String,
+}
+
+impl FromWorld for Profiles {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            shared_path: world
+                .get_resource_or_insert_with(SavePath::default)
+                .0
+                .clone(),
+            active: SHARED_PROFILE.to_string(),
+        }
+    }
+}
+
+impl Profiles {
+    pub fn active(&self) -> &str {
+        &self.active
+    }
+
+    pub fn path_for(&self, name: &str) -> PathBuf {
+        if name == SHARED_PROFILE {
+            return self.shared_path.clone();
+        }
+        self.profiles_dir().join(format!("{}.json", name))
+    }
+
+    /// Every profile with a save, [`SHARED_PROFILE`] first.
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(self.profiles_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                Some(path.file_stem()?.to_str()?.to_string())
+            })
+            .collect();
+        names.sort();
+        names.insert(0, SHARED_PROFILE.to_string());
+        names
+    }
+
+    fn profiles_dir(&self) -> PathBuf {
+        self.shared_path
+            .parent()
+            .map(|dir| dir.join("profiles"))
+            .unwrap_or_else(|| PathBuf::from("profiles"))
+    }
+}
+
+/// What the profile screen shows.
+#[derive(Default)]
+struct ProfileScreen {
+    names: Vec<String>,
+    /// Index in `names`, the entry after the last name creates a new profile.
+    selected: usize,
+    /// Name being typed on the on-screen keyboard, and the selected key.
+    typing: Option<(String, usize)>,
+}
+
+#[derive(Component)]
+struct ProfileScreenRoot;
+
+fn open_profile_screen(profiles: Res<Profiles>, mut screen: ResMut<ProfileScreen>) {
+    let names = profiles.list();
+    *screen = ProfileScreen {
+        selected: names
+            .iter()
+            .position(|name| name == profiles.active())
+            .unwrap_or(0),
+        names,
+        typing: None,
+    };
+}
+
+fn close_profile_screen(mut commands: Commands, q_roots: Query<Entity, With<ProfileScreenRoot>>) {
+    for e in q_roots.iter() {
+        commands.entity(e).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn profile_input(
+    time: Res<Time>,
+    mut screen: ResMut<ProfileScreen>,
+    mut profiles: ResMut<Profiles>,
+    mut save_path: ResMut<SavePath>,
+    save: Res<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut game: ResMut<SimonGame>,
+    mut state: ResMut<State<SimonState>>,
+    mut exit: EventWriter<AppExit>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
+    mut feedback_events: EventWriter<InputReaction>,
+) {
+    for event in arcade_input_events.iter() {
+        if event.value != 1f32 {
+            continue;
+        }
+        let picked = match &mut screen.typing {
+            Some((name, key)) => match type_name(name, key, &event.arcade_input) {
+                Typing::Editing => None,
+                Typing::Cancelled => {
+                    screen.typing = None;
+                    None
+                }
+                Typing::Done => Some(name.clone()),
+            },
+            None => {
+                let entries = screen.names.len() + 1;
+                match event.arcade_input {
+                    ArcadeInput::JoyUp => {
+                        screen.selected = (screen.selected + entries - 1) % entries;
+                        None
+                    }
+                    ArcadeInput::JoyDown => {
+                        screen.selected = (screen.selected + 1) % entries;
+                        None
+                    }
+                    ArcadeInput::JoyButton => match screen.names.get(screen.selected) {
+                        Some(name) => Some(name.clone()),
+                        None => {
+                            screen.typing = Some((String::new(), 0));
+                            None
+                        }
+                    },
+                    ArcadeInput::ButtonLeftSide => {
+                        exit.send(AppExit);
+                        None
+                    }
+                    _ => continue,
+                }
+            }
+        };
+        feedback_events.send(InputReaction {
+            key: event.arcade_input.clone(),
+            feedback: FeedbackType::Menu,
+        });
+        if let Some(name) = picked {
+            let now = time.seconds_since_startup();
+            autosave.flush(now, &save_path, &save);
+            // The save, best scores and stats of the profile are loaded as the path changes.
+            save_path.0 = profiles.path_for(&name);
+            info!("Profile: {}", name);
+            profiles.active = name;
+            *game = SimonGame::default();
+            let _ = state.set(SimonState::Menu);
+            return;
+        }
+    }
+}
+
+enum Typing {
+    Editing,
+    Cancelled,
+    Done,
+}
+
+/// Moves on the on-screen keyboard and types the selected key.
+fn type_name(name: &mut String, key: &mut usize, input: &ArcadeInput) -> Typing {
+    let keys = KEYBOARD.len() + 2;
+    match input {
+        ArcadeInput::JoyLeft => *key = (*key + keys - 1) % keys,
+        ArcadeInput::JoyRight => *key = (*key + 1) % keys,
+        ArcadeInput::JoyUp => *key = (*key + keys - KEYBOARD_COLUMNS) % keys,
+        ArcadeInput::JoyDown => *key = (*key + KEYBOARD_COLUMNS) % keys,
+        ArcadeInput::JoyButton => match *key {
+            DELETE_KEY => {
+                name.pop();
+            }
+            OK_KEY if !name.is_empty() => return Typing::Done,
+            OK_KEY => {}
+            _ if name.len() < MAX_NAME_LENGTH => name.push(KEYBOARD.as_bytes()[*key] as char),
+            _ => {}
+        },
+        ArcadeInput::ButtonLeftSide => return Typing::Cancelled,
+        _ => {}
+    }
+    Typing::Editing
+}
+
+fn draw_profile_screen(
+    mut commands: Commands,
+    theme: Res<DisplayTheme>,
+    screen: Res<ProfileScreen>,
+    q_roots: Query<Entity, With<ProfileScreenRoot>>,
+) {
+    if !screen.is_changed() {
+        return;
+    }
+    for e in q_roots.iter() {
+        commands.entity(e).despawn();
+    }
+    let font = theme.font.clone();
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0f32, 0f32, 0f32, 0.85f32),
+                custom_size: Some(Vec2::splat(10000f32)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0f32, 0f32, 800f32)),
+            ..default()
+        })
+        .insert(ProfileScreenRoot);
+    let mut spawn_text = |value: String, at: Vec2, font_size: f32, color: Color| {
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size,
+                        color,
+                    },
+                    text_alignment,
+                ),
+                transform: Transform::from_translation(at.extend(810f32)),
+                ..default()
+            })
+            .insert(ProfileScreenRoot);
+    };
+    let entry_color = |selected: bool| if selected { Color::GOLD } else { Color::WHITE };
+
+    match &screen.typing {
+        Some((name, key)) => {
+            spawn_text(
+                "New profile".to_string(),
+                Vec2::new(0f32, 300f32),
+                80.0,
+                Color::rgb(0.7, 0.7, 1.0),
+            );
+            spawn_text(
+                format!("{}_", name),
+                Vec2::new(0f32, 180f32),
+                70.0,
+                Color::WHITE,
+            );
+            let labels = KEYBOARD
+                .chars()
+                .map(String::from)
+                .chain(["DEL".to_string(), "OK".to_string()]);
+            for (i, label) in labels.enumerate() {
+                let column = (i % KEYBOARD_COLUMNS) as f32;
+                let row = (i / KEYBOARD_COLUMNS) as f32;
+                spawn_text(
+                    label,
+                    Vec2::new(-405f32 + column * 90f32, 60f32 - row * 90f32),
+                    60.0,
+                    entry_color(i == *key),
+                );
+            }
+        }
+        None => {
+            spawn_text(
+                "Choose a profile".to_string(),
+                Vec2::new(0f32, 300f32),
+                80.0,
+                Color::rgb(0.7, 0.7, 1.0),
+            );
+            let entries = screen
+                .names
+                .iter()
+                .cloned()
+                .chain(["+ New profile".to_string()]);
+            for (i, entry) in entries.enumerate() {
+                let selected = i == screen.selected;
+                spawn_text(
+                    format!("{}{}", if selected { "> " } else { "" }, entry),
+                    Vec2::new(0f32, 150f32 - i as f32 * 80f32),
+                    60.0,
+                    entry_color(selected),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(name: &mut String, key: &mut usize, inputs: &[ArcadeInput]) -> Vec<bool> {
+        inputs
+            .iter()
+            .map(|input| matches!(type_name(name, key, input), Typing::Done))
+            .collect()
+    }
+
+    #[test]
+    fn types_a_name() {
+        let (mut name, mut key) = (String::new(), 0);
+        press(
+            &mut name,
+            &mut key,
+            &[
+                ArcadeInput::JoyButton,
+                ArcadeInput::JoyDown,
+                ArcadeInput::JoyRight,
+                ArcadeInput::JoyButton,
+            ],
+        );
+        assert_eq!(name, "AL");
+    }
+
+    #[test]
+    fn deletes_and_confirms() {
+        let (mut name, mut key) = ("AB".to_string(), DELETE_KEY);
+        press(&mut name, &mut key, &[ArcadeInput::JoyButton]);
+        assert_eq!(name, "A");
+        let done = press(
+            &mut name,
+            &mut key,
+            &[ArcadeInput::JoyRight, ArcadeInput::JoyButton],
+        );
+        assert_eq!(done, vec![false, true]);
+    }
+
+    #[test]
+    fn wraps_around_the_keyboard() {
+        let (mut name, mut key) = (String::new(), 0);
+        press(&mut name, &mut key, &[ArcadeInput::JoyLeft]);
+        assert_eq!(key, OK_KEY);
+        press(&mut name, &mut key, &[ArcadeInput::JoyRight]);
+        assert_eq!(key, 0);
+    }
+}
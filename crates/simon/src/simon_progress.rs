@@ -1,7 +1,12 @@
 use bevy::prelude::*;
-use progress::{ProgressRatio, ProgressScale, ProgressText};
+use progress::{Easing, ProgressBarBuilder, ProgressBinding, ProgressDisplay};
+use rust_arcade_display::DisplayTheme;
 
-use crate::{UserProgress, UserSequence};
+use crate::{
+    simon_game::SimonGame,
+    simon_profile::{Profiles, SHARED_PROFILE},
+    simon_state::SimonState,
+};
 
 pub struct SimonProgressPlugin;
 
@@ -9,8 +14,7 @@ impl Plugin for SimonProgressPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(test_startup)
             .add_system(progress_visibility)
-            .add_system(new_key_visibility)
-            .add_system(progress_update);
+            .add_system(update_title);
     }
 }
 
@@ -18,9 +22,11 @@ impl Plugin for SimonProgressPlugin {
 struct ProgressSimon;
 #[derive(Component)]
 struct ProgressSimonNewKey;
+#[derive(Component)]
+struct ProgressSimonTitle;
 
-fn test_startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+fn test_startup(mut commands: Commands, theme: Res<DisplayTheme>) {
+    let font = theme.font.clone();
     let text_style = TextStyle {
         font,
         font_size: 60.0,
@@ -31,19 +37,21 @@ fn test_startup(mut commands: Commands, asset_server: Res<AssetServer>) {
         horizontal: HorizontalAlign::Center,
     };
 
-    commands.spawn_bundle(Text2dBundle {
-        text: Text::with_section(
-            "'Simon' Game",
-            TextStyle {
-                font_size: 80.0,
-                color: Color::rgb(0.7, 0.7, 1.0),
-                ..text_style.clone()
-            },
-            text_alignment,
-        ),
-        transform: Transform::from_translation(Vec3::new(0f32, 700f32, 500f32)),
-        ..default()
-    });
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "'Simon' Game",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::rgb(0.7, 0.7, 1.0),
+                    ..text_style.clone()
+                },
+                text_alignment,
+            ),
+            transform: Transform::from_translation(Vec3::new(0f32, 700f32, 500f32)),
+            ..default()
+        })
+        .insert(ProgressSimonTitle);
     commands
         .spawn_bundle(Text2dBundle {
             text: Text::with_section(
@@ -63,114 +71,51 @@ fn test_startup(mut commands: Commands, asset_server: Res<AssetServer>) {
         })
         .insert(ProgressSimonNewKey);
 
-    let progress_entity = commands
-        .spawn()
-        .insert(ProgressRatio { ratio: 0.0 })
-        .insert(ProgressSimon)
-        .id();
-    commands
-        .spawn_bundle(Text2dBundle {
-            text: Text::with_section("0/50", text_style.clone(), text_alignment),
-            transform: Transform::from_translation(Vec3::new(0f32, 500f32, 500f32)),
-            ..default()
-        })
-        .insert(ProgressText {
-            max: 50,
-            progress_entity,
-        })
-        .insert(ProgressSimon);
-
-    let width = 500f32;
-    let height = 100f32;
-    commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::BLACK,
-                custom_size: Some(Vec2::new(width, height)),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_translation(Vec3::new(-width / 2f32, 500f32, 498f32)),
-            ..default()
-        })
-        .insert(ProgressSimon);
-    commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::YELLOW_GREEN,
-                custom_size: Some(Vec2::new(width, height)),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_translation(Vec3::new(-width / 2f32, 500f32, 499f32)),
-            ..default()
-        })
-        .insert(ProgressScale {
-            scale_base: Vec3::new(0f32, 1f32, 1f32),
-            scale_mult: Vec3::new(1f32, 0f32, 0f32),
-            progress_entity,
-        })
+    ProgressBarBuilder::new(Vec2::new(500f32, 100f32))
+        .at(Vec3::new(0f32, 500f32, 498f32))
+        .with_colors(Color::BLACK, Color::YELLOW_GREEN)
+        .with_segments(0, 4f32, Color::DARK_GRAY)
+        .with_text("{current}/{max}", 50, text_style)
+        .spawn(&mut commands)
+        // Shows a count of keys, so only smoothed: easing would misreport it.
+        .insert(ProgressDisplay::new(Easing::Linear).with_smoothing(10f32))
+        .insert(
+            ProgressBinding::resource(SimonGame::progress_ratio)
+                .with_max(|world| Some(world.resource::<SimonGame>().sequence().len())),
+        )
         .insert(ProgressSimon);
 }
 
+/// The bar follows the sequence being repeated, the new key text asks for the next key.
+/// Both are hidden behind the menus.
 fn progress_visibility(
-    progress: Res<UserProgress>,
-    sequence: Res<UserSequence>,
-    mut q_toggles: Query<&mut Visibility, With<ProgressSimon>>,
+    state: Res<State<SimonState>>,
+    game: Res<SimonGame>,
+    mut q_progress: Query<&mut Visibility, (With<ProgressSimon>, Without<ProgressSimonNewKey>)>,
+    mut q_new_key: Query<&mut Visibility, With<ProgressSimonNewKey>>,
 ) {
-    if progress.is_changed() {
-        if progress.index == 0 {
-            for mut v in q_toggles.iter_mut() {
-                v.is_visible = true;
-            }
-        }
-        if progress.index == sequence.sequence.len() {
-            for mut v in q_toggles.iter_mut() {
-                v.is_visible = false;
-            }
-        }
+    if !game.is_changed() && !state.is_changed() {
+        return;
     }
-}
-fn new_key_visibility(
-    progress: Res<UserProgress>,
-    sequence: Res<UserSequence>,
-    mut q_toggles: Query<&mut Visibility, With<ProgressSimonNewKey>>,
-) {
-    if progress.is_changed() {
-        if progress.index == 0 {
-            for mut v in q_toggles.iter_mut() {
-                v.is_visible = false;
-            }
-        }
-        if progress.index == sequence.sequence.len() {
-            for mut v in q_toggles.iter_mut() {
-                v.is_visible = true;
-            }
-        }
+    let in_game = !matches!(state.current(), SimonState::Profile | SimonState::Menu);
+    let waiting = game.is_waiting_for_new_key();
+    for mut v in q_progress.iter_mut() {
+        v.is_visible = in_game && !waiting;
+    }
+    for mut v in q_new_key.iter_mut() {
+        v.is_visible = in_game && waiting;
     }
 }
 
-fn progress_update(
-    progress: Res<UserProgress>,
-    sequence: Res<UserSequence>,
-    mut q_ratio: Query<&mut ProgressRatio, With<ProgressSimon>>,
-    mut q_text: Query<&mut ProgressText, With<ProgressSimon>>,
-) {
-    if progress.is_changed() {
-        let len = sequence.sequence.len();
-        let ratio = if len > 0 {
-            progress.index as f32 / len as f32
-        } else {
-            0f32
-        };
-        for mut r in q_ratio.iter_mut() {
-            r.ratio = ratio;
-        }
+/// Names the profile playing, unless it's the shared one.
+fn update_title(profiles: Res<Profiles>, mut q_title: Query<&mut Text, With<ProgressSimonTitle>>) {
+    if !profiles.is_changed() {
+        return;
     }
-    if sequence.is_changed() {
-        let len = sequence.sequence.len();
-        for mut t in q_text.iter_mut() {
-            t.max = len;
-        }
+    for mut text in q_title.iter_mut() {
+        text.sections[0].value = match profiles.active() {
+            SHARED_PROFILE => "'Simon' Game".to_string(),
+            name => format!("'Simon' Game - {}", name),
+        };
     }
 }
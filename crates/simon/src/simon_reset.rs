@@ -1,29 +1,47 @@
-use std::{fs, path::Path};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use progress::{visuals::*, *};
+use rust_arcade::bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent};
+use rust_arcade_display::{DisplayTheme, Reactable};
 
-use bevy::prelude::*;
-use progress::*;
-
-use crate::{UserProgress, UserSequence};
+use crate::{
+    simon_game::{SimonGame, SimonMode},
+    simon_save::{Autosave, SimonSave},
+    simon_state::SimonState,
+};
 
 pub struct SimonResetPlugin;
 
 impl Plugin for SimonResetPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(startup)
-            .insert_resource(ResetState(false))
             .add_system(progress_visibility)
-            .add_system(timer_toggle)
-            .add_system(reset_full.after(timer_toggle));
+            .add_system(place_reset_ring)
+            .add_system_set(SystemSet::on_enter(SimonState::Resetting).with_system(start_timer))
+            .add_system_set(
+                SystemSet::on_update(SimonState::Resetting)
+                    .with_system(cancel_on_release)
+                    .with_system(reset_full),
+            )
+            .add_system_set(SystemSet::on_exit(SimonState::Resetting).with_system(drain_timer));
     }
 }
 
-pub struct ResetState(pub bool);
+pub const RESET_BUTTON: ArcadeInput = ArcadeInput::ButtonFront2;
 
 #[derive(Component)]
 struct SimonReset;
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+/// Hold-to-confirm ring around the reset button.
+#[derive(Component)]
+struct ResetRing;
+
+fn startup(
+    mut commands: Commands,
+    theme: Res<DisplayTheme>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = theme.font.clone();
     let text_style = TextStyle {
         font,
         font_size: 60.0,
@@ -34,102 +52,165 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
         horizontal: HorizontalAlign::Center,
     };
 
-    let progress_entity = commands
-        .spawn()
-        .insert(ProgressRatio { ratio: 0.0 })
-        .insert(ProgressTime {
-            start_time: 0.0,
-            duration: 4.0,
-            active: false,
-        })
+    let progress_entity = ProgressBarBuilder::new(Vec2::new(600f32, 100f32))
+        .at(Vec3::new(0f32, -500f32, 498f32))
+        .with_fill_gradient(vec![
+            (0f32, Color::YELLOW),
+            (0.5f32, Color::ORANGE),
+            (1f32, Color::RED),
+        ])
+        .with_text("Resetting in {remaining}s…", 0, text_style.clone())
+        .with_text_offset(Vec2::new(0f32, 80f32))
+        .spawn(&mut commands)
+        .insert(ProgressTime::new(4.0))
+        .insert(ProgressDisplay::new(Easing::QuadIn))
         .insert(SimonReset)
         .id();
     commands
         .spawn_bundle(Text2dBundle {
-            text: Text::with_section(
-                "Will reset game data...",
-                text_style.clone(),
-                text_alignment,
-            ),
+            text: Text::with_section("Will reset the sequence...", text_style, text_alignment),
             transform: Transform::from_translation(Vec3::new(0f32, -500f32, 500f32)),
             ..default()
         })
         .insert(SimonReset);
 
-    let width = 600f32;
-    let height = 100f32;
+    let ring = ProgressRadial::new(progress_entity, 135f32, 160f32);
     commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::BLACK,
-                custom_size: Some(Vec2::new(width, height)),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_translation(Vec3::new(-width / 2f32, -500f32, 498f32)),
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: meshes.add(ring.mesh(0f32)).into(),
+            material: materials.add(ColorMaterial::from(Color::RED)),
+            transform: Transform::from_translation(Vec3::new(0f32, 0f32, 499f32)),
             ..default()
         })
-        .insert(SimonReset);
-    commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::RED,
-                custom_size: Some(Vec2::new(width, height)),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_translation(Vec3::new(-width / 2f32, -500f32, 499f32)),
-            ..default()
-        })
-        .insert(ProgressScale {
-            scale_base: Vec3::new(0f32, 1f32, 1f32),
-            scale_mult: Vec3::new(1f32, 0f32, 0f32),
+        .insert(ring)
+        .insert(ProgressColor {
             progress_entity,
+            gradient: vec![(0f32, Color::YELLOW), (1f32, Color::RED)],
         })
+        .insert(ResetRing)
         .insert(SimonReset);
 }
 
+fn place_reset_ring(
+    q_reactables: Query<(&Transform, &Reactable), Without<ResetRing>>,
+    mut q_ring: Query<&mut Transform, With<ResetRing>>,
+) {
+    let button = q_reactables
+        .iter()
+        .find(|(_, reactable)| reactable.key == RESET_BUTTON);
+    if let Some((button, _)) = button {
+        for mut transform in q_ring.iter_mut() {
+            transform.translation = button
+                .translation
+                .truncate()
+                .extend(transform.translation.z);
+        }
+    }
+}
+
+/// Shown while the reset button is held, and while the bar drains after it is released.
 fn progress_visibility(
-    progress: Res<ResetState>,
+    state: Res<State<SimonState>>,
+    q_time: Query<&ProgressTime, With<SimonReset>>,
     mut q_toggles: Query<&mut Visibility, With<SimonReset>>,
 ) {
-    if progress.is_changed() {
-        for mut v in q_toggles.iter_mut() {
-            v.is_visible = progress.0;
-            if !v.is_visible {}
-        }
+    let resetting = *state.current() == SimonState::Resetting;
+    let draining = q_time.iter().any(|t| t.active);
+    for mut v in q_toggles.iter_mut() {
+        v.is_visible = resetting || draining;
     }
 }
-fn timer_toggle(
-    time: Res<Time>,
-    progress: Res<ResetState>,
-    mut q: Query<&mut ProgressTime, With<SimonReset>>,
+
+fn start_timer(mut q: Query<&mut ProgressTime, With<SimonReset>>) {
+    for mut p in q.iter_mut() {
+        p.start();
+    }
+}
+
+fn drain_timer(mut q: Query<&mut ProgressTime, With<SimonReset>>) {
+    for mut p in q.iter_mut() {
+        p.reverse();
+    }
+}
+
+/// Goes back to the game once the reset button is up. Events since the press which opened
+/// `Resetting` are still there, so a release in the same batch is seen, and the last event of
+/// the button tells whether it's still held.
+fn cancel_on_release(
+    mut state: ResMut<State<SimonState>>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
 ) {
-    if progress.is_changed() {
-        for mut p in q.iter_mut() {
-            p.active = progress.0;
-            if p.active {
-                p.start_time = time.seconds_since_startup() as f32;
-            }
-        }
+    let released = arcade_input_events
+        .iter()
+        .rev()
+        .find(|event| event.arcade_input == RESET_BUTTON)
+        .is_some_and(|event| event.value == 0f32);
+    if released {
+        let _ = state.pop();
     }
 }
 
+/// Clears the running game, and the saved sequence for [`SimonMode::Extend`] which plays it.
+/// The best length and stats of the save are kept.
 fn reset_full(
-    mut state: ResMut<ResetState>,
-    mut seq: ResMut<UserSequence>,
-    mut prog: ResMut<UserProgress>,
-    q: Query<&ProgressRatio, With<SimonReset>>,
+    clock: Res<Time>,
+    mut state: ResMut<State<SimonState>>,
+    mut game: ResMut<SimonGame>,
+    mut save: ResMut<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut evt_completed: EventReader<ProgressCompleted>,
+    mut q: Query<&mut ProgressTime, With<SimonReset>>,
 ) {
-    if state.0 {
-        if let Ok(progress) = q.get_single() {
-            if progress.ratio >= 1f32 {
-                let file_path = Path::new("./current.json");
-                fs::remove_file(file_path);
-                *seq = default();
-                *prog = default();
-                state.0 = false;
+    for completed in evt_completed.iter() {
+        if let Ok(mut time) = q.get_mut(completed.entity) {
+            time.stop();
+            if clear_game(&mut game, &mut save) {
+                autosave.mark(clock.seconds_since_startup());
             }
+            let _ = state.pop();
+        }
+    }
+}
+
+/// Clears `game`, and the sequence of `save` if `game` extends it. Returns whether `save` changed.
+fn clear_game(game: &mut SimonGame, save: &mut SimonSave) -> bool {
+    game.clear();
+    if game.mode != SimonMode::Extend {
+        return false;
+    }
+    save.set_sequence(&[]);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_sequence() -> SimonSave {
+        let mut save = SimonSave::default();
+        save.set_sequence(&[ArcadeInput::JoyUp, ArcadeInput::JoyDown]);
+        save
+    }
+
+    #[test]
+    fn extend_reset_clears_the_save() {
+        let mut save = saved_sequence();
+        let mut game = SimonGame::new(SimonMode::Extend).with_sequence(save.sequence.clone());
+        assert!(clear_game(&mut game, &mut save));
+        assert!(game.sequence().is_empty());
+        assert!(save.sequence.is_empty());
+        assert_eq!(save.best_length, 2);
+    }
+
+    #[test]
+    fn other_resets_keep_the_save() {
+        for mode in [SimonMode::Classic, SimonMode::Timed, SimonMode::Practice] {
+            let mut save = saved_sequence();
+            let mut game = SimonGame::new(mode);
+            game.add_key(ArcadeInput::JoyLeft);
+            assert!(!clear_game(&mut game, &mut save));
+            assert!(game.sequence().is_empty());
+            assert_eq!(save.sequence.len(), 2);
         }
     }
 }
@@ -0,0 +1,548 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::AppExit, prelude::*, window::WindowCloseRequested};
+use rust_arcade::bevy_rust_arcade::ArcadeInput;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    simon_game::{SimonGame, SimonMode},
+    simon_menu::BestScores,
+    simon_profile::{Profiles, SHARED_PROFILE},
+    simon_state::{SimonState, PAUSE_BUTTON},
+};
+
+/// Keeps the collaborative sequence on disk while it's played.
+pub struct SimonSavePlugin;
+
+impl Plugin for SimonSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavePath>()
+            .init_resource::<SimonSave>()
+            .init_resource::<Autosave>()
+            .add_system_to_stage(CoreStage::PreUpdate, load_save)
+            // After the game systems, so a sequence is compared once it's loaded.
+            .add_system_to_stage(CoreStage::PostUpdate, track_sequence)
+            .add_system_to_stage(CoreStage::PostUpdate, autosave.after(track_sequence))
+            .add_system_to_stage(CoreStage::Last, save_on_exit);
+    }
+}
+
+/// Environment variable read for the save path when `--save-path` isn't passed.
+pub const SAVE_PATH_ENV: &str = "SIMON_SAVE_PATH";
+
+/// Where saves were written before [`SavePath`], relative to the working directory.
+pub const LEGACY_SAVE_PATH: &str = "./current.json";
+
+/// Where the collaborative sequence is saved, shared by everything reading or writing it.
+pub struct SavePath(pub PathBuf);
+
+impl Default for SavePath {
+    /// From the `--save-path <path>` argument, then [`SAVE_PATH_ENV`], then the platform data
+    /// directory.
+    fn default() -> Self {
+        let path = path_from_args(std::env::args())
+            .or_else(|| std::env::var_os(SAVE_PATH_ENV).map(PathBuf::from))
+            .unwrap_or_else(|| data_dir().join("simon").join("current.json"));
+        info!("Save path: {}", path.display());
+        Self(path)
+    }
+}
+
+fn path_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--save-path=") {
+            return Some(value.into());
+        }
+        if arg == "--save-path" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// `$XDG_DATA_HOME`, or its platform equivalent. The working directory if none is found.
+fn data_dir() -> PathBuf {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+    let dir = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME")
+            .or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+    dir.unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Debounces writes of [`SimonSave`]: changes are written once they stop for `debounce`
+/// seconds, or `max_delay` seconds after the first one, and when the app exits.
+pub struct Autosave {
+    pub debounce: f64,
+    pub max_delay: f64,
+    /// When the first change not yet written happened.
+    dirty_since: Option<f64>,
+    last_change: f64,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            debounce: 2.0,
+            max_delay: 30.0,
+            dirty_since: None,
+            last_change: 0.0,
+        }
+    }
+}
+
+impl Autosave {
+    /// Schedules a write of [`SimonSave`].
+    pub fn mark(&mut self, now: f64) {
+        self.dirty_since.get_or_insert(now);
+        self.last_change = now;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_since.is_some()
+    }
+
+    fn is_due(&self, now: f64) -> bool {
+        match self.dirty_since {
+            Some(since) => now - self.last_change >= self.debounce || now - since >= self.max_delay,
+            None => false,
+        }
+    }
+
+    /// Writes pending changes of `save` now.
+    pub fn flush(&mut self, now: f64, path: &SavePath, save: &SimonSave) {
+        if self.is_dirty() {
+            self.write(now, path, save);
+        }
+    }
+
+    /// Writes `save` now, a failed write is retried after the debounce.
+    fn write(&mut self, now: f64, path: &SavePath, save: &SimonSave) {
+        info!("writing to {}", path.0.display());
+        match write(&path.0, save) {
+            Ok(()) => self.dirty_since = None,
+            Err(e) => {
+                error!("couldn't save the sequence: {}", e);
+                self.last_change = now;
+            }
+        }
+    }
+}
+
+/// Reads the save whenever [`SavePath`] changes, at startup or when switching profiles.
+fn load_save(path: Res<SavePath>, profiles: Res<Profiles>, mut save: ResMut<SimonSave>) {
+    if !path.is_changed() {
+        return;
+    }
+    *save = match load(&path.0) {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) if profiles.active() == SHARED_PROFILE => {
+            migrate(Path::new(LEGACY_SAVE_PATH), &path.0).unwrap_or_default()
+        }
+        Ok(None) => SimonSave::default(),
+        Err(e) => {
+            warn!("starting a new save: {}", e);
+            SimonSave::default()
+        }
+    };
+}
+
+/// Moves the save at `legacy` to `path`, the legacy file is renamed so it's only done once.
+fn migrate(legacy: &Path, path: &Path) -> Option<SimonSave> {
+    let save = match load(legacy) {
+        Ok(save) => save?,
+        Err(e) => {
+            warn!("couldn't migrate {}: {}", legacy.display(), e);
+            return None;
+        }
+    };
+    if let Err(e) = write(path, &save) {
+        error!("couldn't migrate {}: {}", legacy.display(), e);
+        return Some(save);
+    }
+    let migrated = with_suffix(legacy, ".migrated");
+    match fs::rename(legacy, &migrated) {
+        Ok(()) => info!("Migrated {} to {}", legacy.display(), path.display()),
+        Err(e) => error!("couldn't rename {}: {}", legacy.display(), e),
+    }
+    Some(save)
+}
+
+/// Copies the extended sequence to the save as soon as it changes, once a game started with
+/// the saved sequence.
+fn track_sequence(
+    time: Res<Time>,
+    state: Res<State<SimonState>>,
+    game: Res<SimonGame>,
+    mut save: ResMut<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+) {
+    if matches!(state.current(), SimonState::Profile | SimonState::Menu)
+        || !game.is_changed()
+        || game.mode != SimonMode::Extend
+        || game.sequence() == save.sequence
+    {
+        return;
+    }
+    save.set_sequence(game.sequence());
+    autosave.mark(time.seconds_since_startup());
+}
+
+fn autosave(
+    time: Res<Time>,
+    path: Res<SavePath>,
+    save: Res<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+) {
+    let now = time.seconds_since_startup();
+    if autosave.is_due(now) {
+        autosave.write(now, &path, &save);
+    }
+}
+
+/// Writes pending changes when quitting from the menu or closing the window.
+fn save_on_exit(
+    time: Res<Time>,
+    path: Res<SavePath>,
+    save: Res<SimonSave>,
+    mut autosave: ResMut<Autosave>,
+    mut ev_exit: EventReader<AppExit>,
+    mut ev_close: EventReader<WindowCloseRequested>,
+) {
+    if ev_exit.iter().count() > 0 || ev_close.iter().count() > 0 {
+        autosave.flush(time.seconds_since_startup(), &path, &save);
+    }
+}
+
+/// Bumped when [`SimonSave`] changes in a way older builds can't read.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Saved collaborative sequence, and what happened to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimonSave {
+    pub version: u32,
+    pub sequence: Vec<ArcadeInput>,
+    /// Longest sequence ever reached, kept through resets of the sequence.
+    pub best_length: usize,
+    #[serde(default)]
+    pub stats: SaveStats,
+    #[serde(default)]
+    pub best_scores: BestScores,
+    /// Seconds since the unix epoch.
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveStats {
+    pub games_played: u64,
+    pub keys_pressed: u64,
+    pub mistakes: u64,
+}
+
+impl Default for SimonSave {
+    fn default() -> Self {
+        let now = now();
+        Self {
+            version: SAVE_VERSION,
+            sequence: Vec::new(),
+            best_length: 0,
+            stats: SaveStats::default(),
+            best_scores: BestScores::default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl SimonSave {
+    /// Replaces the saved sequence, and updates the best length and timestamp.
+    pub fn set_sequence(&mut self, sequence: &[ArcadeInput]) {
+        self.sequence = sequence.to_vec();
+        self.best_length = self.best_length.max(sequence.len());
+        self.updated_at = now();
+    }
+}
+
+/// Every format the save was written in.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SaveFile {
+    Versioned(SimonSave),
+    /// Bare sequence, written before saves were versioned.
+    Legacy(Vec<ArcadeInput>),
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file couldn't be read, it was copied to `backup`.
+    Corrupted {
+        backup: PathBuf,
+        source: serde_json::Error,
+    },
+    /// Written by a newer build, it was copied to `backup`.
+    UnsupportedVersion {
+        version: u32,
+        backup: PathBuf,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "couldn't access the save: {}", e),
+            SaveError::Corrupted { backup, source } => write!(
+                f,
+                "corrupted save ({}), backed up to {}",
+                source,
+                backup.display()
+            ),
+            SaveError::UnsupportedVersion { version, backup } => write!(
+                f,
+                "save version {} is newer than {}, backed up to {}",
+                version,
+                SAVE_VERSION,
+                backup.display()
+            ),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+/// Reads the save at `path`, `None` if there is none yet.
+///
+/// Legacy saves are migrated. Unreadable saves are copied next to `path` before returning an
+/// error, so they aren't lost when the next save overwrites them.
+pub fn load(path: &Path) -> Result<Option<SimonSave>, SaveError> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut save = match serde_json::from_slice(&content) {
+        Ok(SaveFile::Versioned(save)) => save,
+        Ok(SaveFile::Legacy(sequence)) => {
+            let mut save = SimonSave::default();
+            save.set_sequence(&sequence);
+            save
+        }
+        Err(source) => {
+            let backup = backup(path)?;
+            return Err(SaveError::Corrupted { backup, source });
+        }
+    };
+    if save.version > SAVE_VERSION {
+        let backup = backup(path)?;
+        return Err(SaveError::UnsupportedVersion {
+            version: save.version,
+            backup,
+        });
+    }
+    // Saves from before pausing could have it in the sequence, it can't be replayed anymore.
+    if save.sequence.contains(&PAUSE_BUTTON) {
+        warn!(
+            "removing the pause key from the sequence of {}",
+            path.display()
+        );
+        save.sequence.retain(|key| *key != PAUSE_BUTTON);
+    }
+    Ok(Some(save))
+}
+
+/// Writes `save` to a temporary file then renames it over `path`, so an interrupted write
+/// leaves the previous save intact. Missing directories are created.
+pub fn write(path: &Path, save: &SimonSave) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_vec_pretty(save).map_err(io::Error::from)?;
+    let tmp_path = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(&content)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn backup(path: &Path) -> io::Result<PathBuf> {
+    let backup = with_suffix(path, ".bak");
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("simon_save_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn save_path_from_args() {
+        let args = |args: &[&str]| path_from_args(args.iter().map(|a| a.to_string()));
+        assert_eq!(args(&["simon"]), None);
+        assert_eq!(
+            args(&["simon", "--save-path", "a/b.json"]),
+            Some("a/b.json".into())
+        );
+        assert_eq!(
+            args(&["simon", "--save-path=c.json"]),
+            Some("c.json".into())
+        );
+    }
+
+    #[test]
+    fn autosave_is_debounced() {
+        let mut autosave = Autosave::default();
+        assert!(!autosave.is_due(100.0));
+        autosave.mark(0.0);
+        assert!(!autosave.is_due(1.0));
+        assert!(autosave.is_due(2.0));
+        for t in 1..40 {
+            autosave.mark(t as f64);
+        }
+        assert!(!autosave.is_due(29.5));
+        assert!(autosave.is_due(30.0));
+    }
+
+    #[test]
+    fn missing_save_is_none() {
+        assert!(load(&test_path("missing.json")).unwrap().is_none());
+    }
+
+    #[test]
+    fn write_then_load() {
+        let path = test_path("roundtrip.json");
+        let mut save = SimonSave::default();
+        save.set_sequence(&[ArcadeInput::JoyUp, ArcadeInput::ButtonTop1]);
+        save.stats.games_played = 3;
+        save.best_scores.record(SimonMode::Classic, 12);
+        write(&path, &save).unwrap();
+        assert_eq!(load(&path).unwrap(), Some(save));
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn write_creates_directories() {
+        let path = test_path("nested").join("deeper").join("save.json");
+        write(&path, &SimonSave::default()).unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn migrates_legacy_save_once() {
+        let legacy = test_path("legacy_current.json");
+        let path = test_path("migrated").join("current.json");
+        fs::write(&legacy, "[\"JoyUp\",\"JoyDown\"]").unwrap();
+
+        let save = migrate(&legacy, &path).unwrap();
+        assert_eq!(
+            save.sequence,
+            vec![ArcadeInput::JoyUp, ArcadeInput::JoyDown]
+        );
+        assert_eq!(load(&path).unwrap(), Some(save));
+        assert!(!legacy.exists());
+        assert!(with_suffix(&legacy, ".migrated").exists());
+        assert_eq!(migrate(&legacy, &path), None);
+    }
+
+    #[test]
+    fn shorter_save_overwrites_everything() {
+        let path = test_path("shorter.json");
+        let mut save = SimonSave::default();
+        save.set_sequence(&vec![ArcadeInput::JoyUp; 20]);
+        write(&path, &save).unwrap();
+        save.set_sequence(&[ArcadeInput::JoyDown]);
+        write(&path, &save).unwrap();
+        let loaded = load(&path).unwrap().unwrap();
+        assert_eq!(loaded.sequence, vec![ArcadeInput::JoyDown]);
+        assert_eq!(loaded.best_length, 20);
+    }
+
+    #[test]
+    fn migrates_legacy_array() {
+        let path = test_path("legacy.json");
+        let legacy = vec![ArcadeInput::JoyLeft, ArcadeInput::JoyRight];
+        fs::write(&path, serde_json::to_string(&legacy).unwrap()).unwrap();
+        let save = load(&path).unwrap().unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.sequence, legacy);
+        assert_eq!(save.best_length, 2);
+    }
+
+    #[test]
+    fn pause_key_is_removed_from_the_sequence() {
+        let path = test_path("pause_key.json");
+        let legacy = vec![ArcadeInput::JoyLeft, PAUSE_BUTTON, ArcadeInput::JoyRight];
+        fs::write(&path, serde_json::to_string(&legacy).unwrap()).unwrap();
+        let save = load(&path).unwrap().unwrap();
+        assert_eq!(
+            save.sequence,
+            vec![ArcadeInput::JoyLeft, ArcadeInput::JoyRight]
+        );
+    }
+
+    #[test]
+    fn corrupted_save_is_backed_up() {
+        let path = test_path("corrupted.json");
+        fs::write(&path, "[\"JoyUp\"]]garbage").unwrap();
+        match load(&path) {
+            Err(SaveError::Corrupted { backup, .. }) => {
+                assert_eq!(fs::read_to_string(backup).unwrap(), "[\"JoyUp\"]]garbage");
+            }
+            other => panic!("expected a corrupted save, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let path = test_path("newer.json");
+        let save = SimonSave {
+            version: SAVE_VERSION + 1,
+            ..SimonSave::default()
+        };
+        write(&path, &save).unwrap();
+        assert!(matches!(
+            load(&path),
+            Err(SaveError::UnsupportedVersion { .. })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::{ecs::event::Events, prelude::*};
+use particles::GameClock;
+use rust_arcade::bevy_rust_arcade::{ArcadeInput, ArcadeInputEvent};
+use rust_arcade_display::{DisplayTheme, FeedbackType, InputReaction};
+
+use crate::{simon_game::Outcome, GameOutcome};
+
+/// Screens of simon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimonState {
+    /// Profile selection, see [`crate::simon_profile`].
+    Profile,
+    /// Mode selection, see [`crate::simon_menu`].
+    Menu,
+    Playing,
+    /// Short break after a mistake, then back to playing, or to the menu after a game over.
+    Mistake,
+    /// Pushed over [`SimonState::Playing`].
+    Paused,
+    /// Pushed over [`SimonState::Playing`] while the reset button is held.
+    Resetting,
+}
+
+/// Not one of [`crate::simon_classic::ClassicSettings::keys`], nor in the
+/// [`crate::simon_capture::CaptureCombo`]. Collaborative saves from before pausing can have it
+/// in their sequence, [`crate::simon_save::load`] removes it.
+pub const PAUSE_BUTTON: ArcadeInput = ArcadeInput::ButtonTop6;
+
+pub struct SimonStatePlugin;
+
+impl Plugin for SimonStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(SimonState::Profile)
+            .init_resource::<MistakeEnd>()
+            .add_system(enter_mistake)
+            .add_system_set(SystemSet::on_enter(SimonState::Profile).with_system(clear_input))
+            .add_system_set(SystemSet::on_enter(SimonState::Menu).with_system(clear_input))
+            .add_system_set(SystemSet::on_enter(SimonState::Playing).with_system(clear_input))
+            .add_system_set(SystemSet::on_resume(SimonState::Playing).with_system(clear_input))
+            .add_system_set(SystemSet::on_enter(SimonState::Mistake).with_system(clear_input))
+            .add_system_set(SystemSet::on_update(SimonState::Mistake).with_system(end_mistake))
+            .add_system_set(
+                SystemSet::on_enter(SimonState::Paused)
+                    .with_system(clear_input)
+                    .with_system(pause),
+            )
+            .add_system_set(SystemSet::on_update(SimonState::Paused).with_system(pause_input))
+            .add_system_set(SystemSet::on_exit(SimonState::Paused).with_system(resume));
+        // Input isn't cleared when entering `Resetting`: a release right after the press has to
+        // reach `simon_reset`, or the reset would go on with the button up.
+    }
+}
+
+/// Drops inputs pressed before a screen change, so the systems of the new screen don't handle
+/// the press which opened it.
+fn clear_input(mut arcade_input_events: ResMut<Events<ArcadeInputEvent>>) {
+    arcade_input_events.clear();
+}
+
+/// When to leave [`SimonState::Mistake`], and where to.
+#[derive(Default)]
+struct MistakeEnd {
+    at: f64,
+    to_menu: bool,
+}
+
+const MISTAKE_DURATION: f64 = 1.0;
+const GAME_OVER_DURATION: f64 = 2.0;
+
+fn enter_mistake(
+    time: Res<Time>,
+    mut state: ResMut<State<SimonState>>,
+    mut mistake_end: ResMut<MistakeEnd>,
+    mut ev_outcomes: EventReader<GameOutcome>,
+) {
+    for GameOutcome { outcome, .. } in ev_outcomes.iter() {
+        let now = time.seconds_since_startup();
+        match outcome {
+            Outcome::Mistake { .. } => {
+                *mistake_end = MistakeEnd {
+                    at: now + MISTAKE_DURATION,
+                    to_menu: false,
+                }
+            }
+            Outcome::GameOver { .. } => {
+                *mistake_end = MistakeEnd {
+                    at: now + GAME_OVER_DURATION,
+                    to_menu: true,
+                }
+            }
+            _ => continue,
+        }
+        if *state.current() == SimonState::Playing {
+            let _ = state.set(SimonState::Mistake);
+        }
+    }
+}
+
+fn end_mistake(
+    time: Res<Time>,
+    mistake_end: Res<MistakeEnd>,
+    mut state: ResMut<State<SimonState>>,
+) {
+    if time.seconds_since_startup() >= mistake_end.at {
+        let _ = state.set(if mistake_end.to_menu {
+            SimonState::Menu
+        } else {
+            SimonState::Playing
+        });
+    }
+}
+
+#[derive(Component)]
+struct PausedText;
+
+fn pause(mut commands: Commands, theme: Res<DisplayTheme>, mut clock: ResMut<GameClock>) {
+    clock.pause();
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "Paused",
+                TextStyle {
+                    font: theme.font.clone(),
+                    font_size: 100.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0f32, 0f32, 810f32)),
+            ..default()
+        })
+        .insert(PausedText);
+}
+
+fn pause_input(
+    mut state: ResMut<State<SimonState>>,
+    mut arcade_input_events: EventReader<ArcadeInputEvent>,
+    mut feedback_events: EventWriter<InputReaction>,
+) {
+    for event in arcade_input_events.iter() {
+        if event.value == 1f32 && event.arcade_input == PAUSE_BUTTON {
+            feedback_events.send(InputReaction {
+                key: event.arcade_input.clone(),
+                feedback: FeedbackType::Menu,
+            });
+            let _ = state.pop();
+            return;
+        }
+    }
+}
+
+fn resume(
+    mut commands: Commands,
+    mut clock: ResMut<GameClock>,
+    q_text: Query<Entity, With<PausedText>>,
+) {
+    clock.resume();
+    for e in q_text.iter() {
+        commands.entity(e).despawn();
+    }
+}